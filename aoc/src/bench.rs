@@ -0,0 +1,150 @@
+use crate::days;
+use anyhow::Context;
+use aoc_common::{workspace_dir, AocResult, InputProvider, Stats, YEAR};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Where `aoc bench` keeps its results by default; it's machine specific, so not committed.
+pub fn default_history_path() -> PathBuf {
+    workspace_dir().join("bench_history.json")
+}
+
+/// One stage of one benchmark run, as stored in the history file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Record {
+    /// Seconds since the unix epoch.
+    pub timestamp: u64,
+    /// Short hash of `HEAD` at the time, with a `-dirty` suffix for uncommitted changes.
+    pub commit: Option<String>,
+    pub day: u8,
+    pub variant: String,
+    pub stage: String,
+    #[serde(flatten)]
+    pub stats: Stats,
+}
+
+/// All earlier results, oldest first.
+#[derive(Debug, Default)]
+pub struct History {
+    pub records: Vec<Record>,
+}
+
+impl History {
+    pub fn load(path: &Path) -> AocResult<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let text =
+            fs::read_to_string(path).with_context(|| format!("Can't read {}", path.display()))?;
+        let records = serde_json::from_str(&text)
+            .with_context(|| format!("Invalid benchmark history in {}", path.display()))?;
+
+        Ok(Self { records })
+    }
+
+    pub fn save(&self, path: &Path) -> AocResult<()> {
+        let json = serde_json::to_string_pretty(&self.records)?;
+        fs::write(path, json + "\n").with_context(|| format!("Can't write {}", path.display()))
+    }
+
+    /// The most recent result for the same stage of the same day and input.
+    pub fn previous(&self, day: u8, variant: &str, stage: &str) -> Option<&Record> {
+        self.records
+            .iter()
+            .rev()
+            .find(|record| record.day == day && record.variant == variant && record.stage == stage)
+    }
+}
+
+fn current_commit() -> Option<String> {
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .args(args)
+            .current_dir(workspace_dir())
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+
+    let commit = git(&["rev-parse", "--short", "HEAD"])?;
+    let dirty = git(&["status", "--porcelain"]).is_some_and(|status| !status.is_empty());
+
+    Some(if dirty { commit + "-dirty" } else { commit })
+}
+
+/// "+12.3%" when `now` is slower than `before`, "-4.0%" when it's faster.
+fn change(before: &Stats, now: &Stats) -> String {
+    let ratio = now.median_ns as f64 / before.median_ns.max(1) as f64;
+    format!("{:+.1}%", (ratio - 1.0) * 100.0)
+}
+
+/// Benchmark every solved day (or just `day`) on `variant`, compare each stage's median with the
+/// previous run in the history, and append the new results unless `save` is off.
+pub fn bench(
+    day: Option<u8>,
+    variant: &str,
+    runs: usize,
+    history_path: &Path,
+    save: bool,
+) -> AocResult<()> {
+    let provider = InputProvider::from_env();
+    let mut history = History::load(history_path)?;
+
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let commit = current_commit();
+
+    let explicit = day.is_some();
+    let days = match day {
+        Some(day) => vec![day],
+        None => days::DAYS.to_vec(),
+    };
+
+    let mut records = vec![];
+    for day in days {
+        let path = match provider.resolve(YEAR, day, variant) {
+            Ok(path) => path,
+            // a single day was asked for explicitly, so it not being there is an error:
+            Err(err) if explicit => return Err(err),
+            Err(err) => {
+                println!("day {day:>2} skipped: {err:#}");
+                continue;
+            }
+        };
+
+        let timings = days::bench(day, &path, runs).with_context(|| format!("Day {day} failed"))?;
+
+        for (stage, stats) in timings.stages() {
+            let compared = match history.previous(day, variant, stage) {
+                Some(previous) => format!(
+                    " ({} vs {})",
+                    change(&previous.stats, &stats),
+                    previous.commit.as_deref().unwrap_or("?")
+                ),
+                None => String::new(),
+            };
+            println!("day {day:>2} {stage:<5} {stats}{compared}");
+
+            records.push(Record {
+                timestamp,
+                commit: commit.clone(),
+                day,
+                variant: variant.to_string(),
+                stage: stage.to_string(),
+                stats,
+            });
+        }
+    }
+
+    if save && !records.is_empty() {
+        history.records.extend(records);
+        history.save(history_path)?;
+        println!("Saved to {}", history_path.display());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use aoc_common::{Answer, AocResult, DayBench, Explain, PuzzleInput, Solution};
+use std::path::Path;
+
+/// Every solved day, mapped to its `Solution`; `DAYS` and the dispatchers are generated from this list.
+macro_rules! days {
+    ($($day:literal => $solution:ty,)*) => {
+        pub const DAYS: &[u8] = &[$($day),*];
+
+        /// Dispatch to the `Solution` of the given day.
+        pub fn solve(day: u8, part: u8, input: &PuzzleInput) -> AocResult<Answer> {
+            match day {
+                $($day => <$solution>::solve(part, input),)*
+                _ => anyhow::bail!("Day {day} isn't solved (yet)"),
+            }
+        }
+
+        /// The report of the given day's `Solution::explain`, with the default config.
+        pub fn explain(day: u8, input: &PuzzleInput, options: &Explain) -> AocResult<String> {
+            match day {
+                $($day => {
+                    let config = Default::default();
+                    <$solution>::explain(&<$solution>::parse(input)?, &config, options)
+                })*
+                _ => anyhow::bail!("Day {day} isn't solved (yet)"),
+            }
+        }
+
+        /// Time each stage of the given day's `Solution` on the input at `path`.
+        pub fn bench(day: u8, path: &Path, runs: usize) -> AocResult<DayBench> {
+            match day {
+                $($day => aoc_common::bench::<$solution>(path, runs),)*
+                _ => anyhow::bail!("Day {day} isn't solved (yet)"),
+            }
+        }
+    };
+}
+
+days! {
+    1 => day_1::Day1,
+    2 => day_2::Day2,
+    3 => day_3::Day3,
+    4 => day_4::Day4,
+    5 => day_5::Day5,
+    6 => day_6::Day6,
+    7 => day_7::Day7,
+    8 => day_8::Day8,
+    9 => day_9::Day9,
+    10 => day_10::Day10,
+    11 => day_11::Day11,
+    12 => day_12::Day12,
+    14 => day_14::Day14,
+    15 => day_15::Day15,
+    17 => day_17::Day17,
+    19 => day_19::Day19,
+    22 => day_22::Day22,
+    23 => day_23::Day23,
+}
@@ -0,0 +1,174 @@
+mod bench;
+mod days;
+mod scaffold;
+mod verify;
+
+use anyhow::Context;
+use aoc_common::{AocResult, Explain, Format, InputProvider, PuzzleInput, REAL_INPUT, YEAR};
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+use std::time::Instant;
+
+#[derive(Parser)]
+#[command(name = "aoc", about = "Advent of Code 2024 solutions")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Solve one part of one day and print the answer with its runtime
+    Run {
+        #[arg(long)]
+        day: u8,
+
+        #[arg(long, value_parser = clap::value_parser!(u8).range(1..=2))]
+        part: u8,
+
+        /// Which of the day's inputs to use, e.g. `example` or `example2`
+        #[arg(long, default_value = REAL_INPUT)]
+        variant: String,
+
+        /// Read this file (or `-` for stdin) instead of one of the day's inputs
+        #[arg(long)]
+        input: Option<PathBuf>,
+    },
+    /// Solve everything with a recorded answer in `answers.toml` and report what changed
+    Verify {
+        /// Only this day, instead of all of them
+        #[arg(long)]
+        day: Option<u8>,
+
+        /// Only this input variant, e.g. `example`
+        #[arg(long)]
+        variant: Option<String>,
+    },
+    /// Time parse, part 1 and part 2 of every solved day, and compare with earlier runs
+    Bench {
+        /// Only this day, instead of all of them
+        #[arg(long)]
+        day: Option<u8>,
+
+        #[arg(long, default_value = REAL_INPUT)]
+        variant: String,
+
+        /// How many timed runs per stage
+        #[arg(long, default_value_t = 10)]
+        runs: usize,
+
+        /// The JSON file results are compared with and appended to (default: `bench_history.json`)
+        #[arg(long)]
+        history: Option<PathBuf>,
+
+        /// Only compare, don't add this run to the history
+        #[arg(long)]
+        no_save: bool,
+    },
+    /// Print a day's report on how its answers come about (not every day has one)
+    Explain {
+        #[arg(long)]
+        day: u8,
+
+        /// Which of the day's inputs to use, e.g. `example` or `example2`
+        #[arg(long, default_value = REAL_INPUT)]
+        variant: String,
+
+        /// Read this file (or `-` for stdin) instead of one of the day's inputs
+        #[arg(long)]
+        input: Option<PathBuf>,
+
+        /// `text`, `json`, or `dot` for the days with a graph
+        #[arg(long, default_value_t = Format::Text)]
+        format: Format,
+
+        /// Color the text report, e.g. day 4's matches
+        #[arg(long)]
+        color: bool,
+
+        /// Also draw the report as a PNG here, for the days that can (day 4 adds the part to the name)
+        #[arg(long)]
+        png: Option<PathBuf>,
+
+        /// Single out one (1-based) item, e.g. one of day 5's updates: only its report, or its broken rules in red
+        #[arg(long)]
+        focus: Option<usize>,
+
+        /// Leave out everything but `--focus`, e.g. the rules between other pages in day 5's graph
+        #[arg(long, requires = "focus")]
+        only_focus: bool,
+    },
+    /// Start a new day from the `day_0` template, with its inputs directory and answers file
+    New {
+        #[arg(value_parser = clap::value_parser!(u8).range(1..=25))]
+        day: u8,
+    },
+}
+
+/// `--input` if given, otherwise the day's `--variant`.
+fn load(day: u8, variant: &str, input: Option<PathBuf>) -> AocResult<PuzzleInput> {
+    match input {
+        Some(path) if path.as_os_str() == "-" => PuzzleInput::stdin().context("Can't read stdin"),
+        Some(path) => {
+            PuzzleInput::open(&path).with_context(|| format!("Can't read {}", path.display()))
+        }
+        None => InputProvider::from_env().open(YEAR, day, variant),
+    }
+}
+
+fn run(day: u8, part: u8, variant: &str, input: Option<PathBuf>) -> AocResult<()> {
+    let input = load(day, variant, input)?;
+
+    let start = Instant::now();
+    let answer = days::solve(day, part, &input)?;
+    let elapsed = start.elapsed();
+
+    println!("Day {day} part {part}: {answer}");
+    println!("Took {elapsed:?}");
+
+    Ok(())
+}
+
+fn main() -> AocResult<()> {
+    match Cli::parse().command {
+        Command::Run {
+            day,
+            part,
+            variant,
+            input,
+        } => run(day, part, &variant, input),
+        Command::Verify { day, variant } => verify::verify(day, variant.as_deref()),
+        Command::Bench {
+            day,
+            variant,
+            runs,
+            history,
+            no_save,
+        } => {
+            let history = history.unwrap_or_else(bench::default_history_path);
+            bench::bench(day, &variant, runs, &history, !no_save)
+        }
+        Command::Explain {
+            day,
+            variant,
+            input,
+            format,
+            color,
+            png,
+            focus,
+            only_focus,
+        } => {
+            let input = load(day, &variant, input)?;
+            let options = Explain {
+                format,
+                color,
+                png,
+                focus,
+                only_focus,
+            };
+            print!("{}", days::explain(day, &input, &options)?);
+            Ok(())
+        }
+        Command::New { day } => scaffold::new_day(day),
+    }
+}
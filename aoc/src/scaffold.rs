@@ -0,0 +1,144 @@
+use anyhow::Context;
+use aoc_common::{workspace_dir, AocResult, InputProvider, ANSWERS_FILE, YEAR};
+use std::fs;
+use std::path::Path;
+
+/// The crate every new day starts out as.
+const TEMPLATE: &str = "day_0";
+
+const ANSWERS_STUB: &str = "\
+[example]
+# part1 =
+# part2 =
+
+[input]
+# part1 =
+# part2 =
+";
+
+/// Create `day_<day>` from the `day_0` template, hook it up to the runner and create its inputs directory,
+/// with an empty `example.txt` to paste the example into and an `answers.toml` to fill in.
+///
+/// `input.txt` is left out on purpose: an empty one would stop it from being downloaded.
+pub fn new_day(day: u8) -> AocResult<()> {
+    anyhow::ensure!(
+        (1..=25).contains(&day),
+        "There's no day {day} in the calendar"
+    );
+
+    let root = workspace_dir();
+    let crate_dir = root.join(format!("day_{day}"));
+    anyhow::ensure!(
+        !crate_dir.exists(),
+        "{} already exists",
+        crate_dir.display()
+    );
+
+    // update the runner first, so nothing is left half done when it can't be:
+    let runner = root.join("aoc");
+    let manifest = read(&runner.join("Cargo.toml"))?;
+    let manifest = insert_sorted(
+        &manifest,
+        &format!("day_{day} = {{ path = \"../day_{day}\" }}"),
+        day,
+        |line| line.strip_prefix("day_")?.split_once(' ')?.0.parse().ok(),
+    )?;
+    let days = read(&runner.join("src/days.rs"))?;
+    let days = insert_sorted(
+        &days,
+        &format!("    {day} => day_{day}::Day{day},"),
+        day,
+        |line| line.trim().split_once(" => day_")?.0.parse().ok(),
+    )?;
+
+    let template = root.join(TEMPLATE);
+    let cargo_toml = read(&template.join("Cargo.toml"))?.replace(
+        &format!("name = \"{TEMPLATE}\""),
+        &format!("name = \"day_{day}\""),
+    );
+    let main_rs = read(&template.join("main.rs"))?
+        .replace("const DAY: u8 = 0;", &format!("const DAY: u8 = {day};"))
+        .replace("Day0", &format!("Day{day}"));
+
+    fs::create_dir_all(&crate_dir)?;
+    write(&crate_dir.join("Cargo.toml"), &cargo_toml)?;
+    write(&crate_dir.join("main.rs"), &main_rs)?;
+    write(&runner.join("Cargo.toml"), &manifest)?;
+    write(&runner.join("src/days.rs"), &days)?;
+    println!("Created {}", crate_dir.display());
+
+    let inputs = InputProvider::from_env().day_dir(YEAR, day);
+    fs::create_dir_all(&inputs)?;
+    for (name, contents) in [("example.txt", ""), (ANSWERS_FILE, ANSWERS_STUB)] {
+        let path = inputs.join(name);
+        if !path.exists() {
+            write(&path, contents)?;
+            println!("Created {}", path.display());
+        }
+    }
+
+    Ok(())
+}
+
+fn read(path: &Path) -> AocResult<String> {
+    fs::read_to_string(path).with_context(|| format!("Can't read {}", path.display()))
+}
+
+fn write(path: &Path, contents: &str) -> AocResult<()> {
+    fs::write(path, contents).with_context(|| format!("Can't write {}", path.display()))
+}
+
+/// Add `entry` to the lines that `day_of` recognizes as one per day, keeping those in order.
+fn insert_sorted(
+    text: &str,
+    entry: &str,
+    day: u8,
+    day_of: impl Fn(&str) -> Option<u8>,
+) -> AocResult<String> {
+    let mut lines: Vec<&str> = text.lines().collect();
+    let days: Vec<(usize, u8)> = lines
+        .iter()
+        .enumerate()
+        .filter_map(|(index, line)| Some((index, day_of(line)?)))
+        .collect();
+
+    let Some(&(last, _)) = days.last() else {
+        anyhow::bail!("Can't find where to add day {day}");
+    };
+    anyhow::ensure!(
+        days.iter().all(|&(_, existing)| existing != day),
+        "Day {day} is already registered"
+    );
+
+    let index = days
+        .iter()
+        .find(|&&(_, existing)| existing > day)
+        .map_or(last + 1, |&(index, _)| index);
+    lines.insert(index, entry);
+
+    Ok(lines.join("\n") + "\n")
+}
+
+// -- tests --
+
+#[test]
+fn test_insert_sorted() {
+    let day_of = |line: &str| line.strip_prefix("day_")?.split_once(' ')?.0.parse().ok();
+    let manifest = "[dependencies]\nanyhow = \"1\"\n\nday_2 = 2\nday_10 = 10\n";
+
+    assert_eq!(
+        insert_sorted(manifest, "day_4 = 4", 4, day_of).expect("Room for day 4"),
+        "[dependencies]\nanyhow = \"1\"\n\nday_2 = 2\nday_4 = 4\nday_10 = 10\n"
+    );
+    assert_eq!(
+        insert_sorted(manifest, "day_1 = 1", 1, day_of).expect("Room for day 1"),
+        "[dependencies]\nanyhow = \"1\"\n\nday_1 = 1\nday_2 = 2\nday_10 = 10\n"
+    );
+    assert_eq!(
+        insert_sorted(manifest, "day_11 = 11", 11, day_of).expect("Room for day 11"),
+        "[dependencies]\nanyhow = \"1\"\n\nday_2 = 2\nday_10 = 10\nday_11 = 11\n"
+    );
+
+    assert!(insert_sorted(manifest, "day_2 = 2", 2, day_of).is_err());
+    assert!(insert_sorted("[dependencies]\n", "day_2 = 2", 2, day_of).is_err());
+}
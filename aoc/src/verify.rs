@@ -0,0 +1,65 @@
+use crate::days;
+use aoc_common::{AnswerRegistry, AocResult, InputProvider, Verdict, ANSWERS_FILE, YEAR};
+use std::time::Instant;
+
+/// Solve every recorded (day, variant, part) and compare against `answers.toml`.
+/// Inputs that aren't available are skipped; any failure or regression makes the whole run fail.
+pub fn verify(day: Option<u8>, variant: Option<&str>) -> AocResult<()> {
+    let provider = InputProvider::from_env();
+    let days = match day {
+        Some(day) => day..=day,
+        None => 1..=25,
+    };
+
+    let (mut checked, mut skipped, mut failed) = (0, 0, 0);
+    for day in days {
+        let answers_file = provider.day_dir(YEAR, day).join(ANSWERS_FILE);
+        if !answers_file.exists() {
+            continue;
+        }
+        let registry = AnswerRegistry::load(&answers_file)?;
+
+        for (name, answers) in &registry.variants {
+            if variant.is_some_and(|variant| variant != name) {
+                continue;
+            }
+
+            for part in 1..=2 {
+                let Some(expected) = answers.part(part) else {
+                    continue;
+                };
+                let input = match provider.open(YEAR, day, name) {
+                    Ok(input) => input,
+                    Err(err) => {
+                        println!("day {day:>2} part {part} {name:<10} skipped: {err:#}");
+                        skipped += 1;
+                        continue;
+                    }
+                };
+
+                let start = Instant::now();
+                let (verdict, answer) = match days::solve(day, part, &input) {
+                    Ok(answer) => (expected.check(&answer), answer.to_string()),
+                    Err(err) => {
+                        let reason = format!("{err:#}");
+                        (Verdict::Fail { reason }, "-".to_string())
+                    }
+                };
+                let elapsed = start.elapsed();
+
+                println!("day {day:>2} part {part} {name:<10} {verdict}: {answer} ({elapsed:?})");
+                checked += 1;
+                if !verdict.is_ok() {
+                    failed += 1;
+                }
+            }
+        }
+    }
+
+    println!("{checked} checked, {failed} failed, {skipped} skipped");
+    if failed > 0 {
+        anyhow::bail!("{failed} of {checked} answers are wrong");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,164 @@
+use crate::{Answer, AocResult, PuzzleInput, Solution};
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// The stages of a solution that get timed separately.
+pub const STAGES: [&str; 3] = ["parse", "part1", "part2"];
+
+/// Summary of a number of timed runs, in nanoseconds so it survives a round trip through JSON.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Stats {
+    pub runs: usize,
+    pub min_ns: u64,
+    pub median_ns: u64,
+    pub mean_ns: u64,
+}
+
+impl Stats {
+    pub fn from_samples(mut samples: Vec<Duration>) -> Self {
+        samples.sort();
+        let nanos = |duration: Duration| duration.as_nanos() as u64;
+        let total: u64 = samples.iter().copied().map(nanos).sum();
+
+        Self {
+            runs: samples.len(),
+            min_ns: samples.first().copied().map_or(0, nanos),
+            median_ns: samples.get(samples.len() / 2).copied().map_or(0, nanos),
+            mean_ns: total / samples.len().max(1) as u64,
+        }
+    }
+
+    pub fn median(&self) -> Duration {
+        Duration::from_nanos(self.median_ns)
+    }
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "median {:?}, min {:?}, mean {:?} over {} runs",
+            self.median(),
+            Duration::from_nanos(self.min_ns),
+            Duration::from_nanos(self.mean_ns),
+            self.runs
+        )
+    }
+}
+
+/// How long each stage of one day took on one input; parts without an answer aren't timed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DayBench {
+    pub parse: Stats,
+    pub part1: Option<Stats>,
+    pub part2: Option<Stats>,
+}
+
+impl DayBench {
+    /// (stage, stats) in the order of `STAGES`, for the stages that were timed.
+    pub fn stages(&self) -> Vec<(&'static str, Stats)> {
+        [Some(self.parse), self.part1, self.part2]
+            .into_iter()
+            .zip(STAGES)
+            .filter_map(|(stats, stage)| Some((stage, stats?)))
+            .collect()
+    }
+}
+
+/// Time `parse`, `part1` and `part2` of a `Solution` separately, `runs` times each (after one untimed warm-up).
+/// The file is only read once, so disk caching doesn't skew the parse time.
+/// The parts in `Solution::UNSOLVED` aren't run at all, and any other part that comes back `Unsolved`
+/// in the warm-up is skipped from then on.
+pub fn bench<S: Solution>(path: &Path, runs: usize) -> AocResult<DayBench> {
+    let config = S::Config::default();
+    let (mut parse, mut part1, mut part2) = (vec![], vec![], vec![]);
+    let (mut solved1, mut solved2) = (!S::UNSOLVED.contains(&1), !S::UNSOLVED.contains(&2));
+
+    let text = PuzzleInput::open(path).with_context(|| format!("Can't read {}", path.display()))?;
+    for _ in 0..=runs {
+        let start = Instant::now();
+        let input = S::parse(&text)?;
+        let parsed = start.elapsed();
+
+        if solved1 {
+            let start = Instant::now();
+            let answer = S::part1(&input, &config)?;
+            part1.push(start.elapsed());
+            solved1 = answer != Answer::Unsolved;
+        }
+
+        if solved2 {
+            let start = Instant::now();
+            let answer = S::part2(&input, &config)?;
+            part2.push(start.elapsed());
+            solved2 = answer != Answer::Unsolved;
+        }
+
+        parse.push(parsed);
+    }
+
+    // drop the warm-up:
+    let timed = |samples: Vec<Duration>, solved: bool| {
+        solved.then(|| Stats::from_samples(samples[1..].to_vec()))
+    };
+
+    parse.remove(0);
+    Ok(DayBench {
+        parse: Stats::from_samples(parse),
+        part1: timed(part1, solved1),
+        part2: timed(part2, solved2),
+    })
+}
+
+// -- tests --
+
+#[test]
+fn test_stats_from_samples() {
+    let samples = [30, 10, 20, 100].map(Duration::from_nanos).to_vec();
+    let stats = Stats::from_samples(samples);
+
+    assert_eq!(stats.runs, 4);
+    assert_eq!(stats.min_ns, 10);
+    assert_eq!(stats.median_ns, 30);
+    assert_eq!(stats.mean_ns, 40);
+
+    assert_eq!(Stats::from_samples(vec![]).mean_ns, 0);
+}
+
+#[test]
+fn test_bench_leaves_out_unsolved_parts() {
+    struct Half;
+
+    impl Solution for Half {
+        const DAY: u8 = 0;
+        const UNSOLVED: &'static [u8] = &[2];
+
+        type Input = usize;
+        type Config = ();
+
+        fn parse(input: &PuzzleInput) -> AocResult<usize> {
+            Ok(input.lines().count())
+        }
+
+        fn part1(input: &usize, _config: &()) -> AocResult<Answer> {
+            Ok((*input as i64).into())
+        }
+
+        fn part2(_input: &usize, _config: &()) -> AocResult<Answer> {
+            panic!("Shouldn't run an unsolved part")
+        }
+    }
+
+    let path = std::env::temp_dir().join(format!("aoc_bench_{}.txt", std::process::id()));
+    std::fs::write(&path, "1\n2\n").expect("Temp dir");
+
+    let timings = bench::<Half>(&path, 3).expect("Benchable");
+    assert_eq!(timings.parse.runs, 3);
+    assert_eq!(timings.part1.map(|stats| stats.runs), Some(3));
+    assert_eq!(timings.part2, None);
+
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,212 @@
+use std::fmt::{self, Display};
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// A position (or offset) on a grid, in (row, col) order: `row` grows downwards and `col` to the right,
+/// so (0, 0) is the top left of the input.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point {
+    pub row: i64,
+    pub col: i64,
+}
+
+impl Point {
+    pub const ORIGIN: Self = Self::new(0, 0);
+
+    pub const fn new(row: i64, col: i64) -> Self {
+        Self { row, col }
+    }
+
+    pub fn manhattan(&self, other: &Self) -> i64 {
+        (self.row - other.row).abs() + (self.col - other.col).abs()
+    }
+}
+
+impl From<(i64, i64)> for Point {
+    fn from((row, col): (i64, i64)) -> Self {
+        Self::new(row, col)
+    }
+}
+
+impl Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.row, self.col)
+    }
+}
+
+impl Add for Point {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.row + other.row, self.col + other.col)
+    }
+}
+
+impl Sub for Point {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.row - other.row, self.col - other.col)
+    }
+}
+
+impl Mul<i64> for Point {
+    type Output = Self;
+
+    fn mul(self, factor: i64) -> Self {
+        Self::new(self.row * factor, self.col * factor)
+    }
+}
+
+impl Neg for Point {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.row, -self.col)
+    }
+}
+
+impl AddAssign for Point {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Point {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+/// One step in `direction`:
+impl Add<Direction> for Point {
+    type Output = Self;
+
+    fn add(self, direction: Direction) -> Self {
+        self + direction.delta()
+    }
+}
+
+impl AddAssign<Direction> for Point {
+    fn add_assign(&mut self, direction: Direction) {
+        *self = *self + direction;
+    }
+}
+
+/// The 8 compass directions, in clockwise order starting at `Up`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Direction {
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+impl Direction {
+    pub const CARDINAL: [Self; 4] = [Self::Up, Self::Right, Self::Down, Self::Left];
+    pub const DIAGONAL: [Self; 4] = [Self::UpRight, Self::DownRight, Self::DownLeft, Self::UpLeft];
+    pub const ALL: [Self; 8] = [
+        Self::Up,
+        Self::UpRight,
+        Self::Right,
+        Self::DownRight,
+        Self::Down,
+        Self::DownLeft,
+        Self::Left,
+        Self::UpLeft,
+    ];
+
+    /// `^`, `>`, `v` or `<`, as used for guards, robots and move lists.
+    pub fn from_char(char: char) -> Option<Self> {
+        match char {
+            '^' => Some(Self::Up),
+            '>' => Some(Self::Right),
+            'v' => Some(Self::Down),
+            '<' => Some(Self::Left),
+            _ => None,
+        }
+    }
+
+    /// Inverse of `from_char`; diagonals have no arrow.
+    pub fn as_char(&self) -> Option<char> {
+        match self {
+            Self::Up => Some('^'),
+            Self::Right => Some('>'),
+            Self::Down => Some('v'),
+            Self::Left => Some('<'),
+            _ => None,
+        }
+    }
+
+    pub fn delta(&self) -> Point {
+        match self {
+            Self::Up => Point::new(-1, 0),
+            Self::UpRight => Point::new(-1, 1),
+            Self::Right => Point::new(0, 1),
+            Self::DownRight => Point::new(1, 1),
+            Self::Down => Point::new(1, 0),
+            Self::DownLeft => Point::new(1, -1),
+            Self::Left => Point::new(0, -1),
+            Self::UpLeft => Point::new(-1, -1),
+        }
+    }
+
+    pub fn is_diagonal(&self) -> bool {
+        Self::DIAGONAL.contains(self)
+    }
+
+    /// Rotate clockwise by `eighths` * 45 degrees.
+    fn rotate(&self, eighths: usize) -> Self {
+        Self::ALL[(*self as usize + eighths) % 8]
+    }
+
+    /// 90 degrees clockwise.
+    pub fn turn_right(&self) -> Self {
+        self.rotate(2)
+    }
+
+    /// 90 degrees counter-clockwise.
+    pub fn turn_left(&self) -> Self {
+        self.rotate(6)
+    }
+
+    pub fn reverse(&self) -> Self {
+        self.rotate(4)
+    }
+}
+
+// -- tests --
+
+#[test]
+fn test_point_arithmetic() {
+    let a = Point::new(1, 2);
+    let b = Point::new(4, -2);
+
+    assert_eq!(a + b, Point::new(5, 0));
+    assert_eq!(b - a, Point::new(3, -4));
+    assert_eq!(a * 3, Point::new(3, 6));
+    assert_eq!(-a, Point::new(-1, -2));
+    assert_eq!(a.manhattan(&b), 7);
+    assert_eq!(a + Direction::Up, Point::new(0, 2));
+    assert_eq!(a + Direction::DownLeft, Point::new(2, 1));
+}
+
+#[test]
+fn test_direction_turns() {
+    assert_eq!(Direction::Up.turn_right(), Direction::Right);
+    assert_eq!(Direction::Up.turn_left(), Direction::Left);
+    assert_eq!(Direction::Left.reverse(), Direction::Right);
+    assert_eq!(Direction::UpLeft.turn_right(), Direction::UpRight);
+    assert_eq!(Direction::from_char('v'), Some(Direction::Down));
+    assert_eq!(Direction::from_char('x'), None);
+
+    for direction in Direction::ALL {
+        assert_eq!(
+            direction.delta() + direction.reverse().delta(),
+            Point::ORIGIN
+        );
+    }
+}
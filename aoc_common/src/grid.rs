@@ -0,0 +1,263 @@
+use crate::geometry::{Direction, Point};
+use crate::ParseError;
+use std::fmt::{self, Display};
+use std::ops::{Index, IndexMut};
+
+/// A dense, row-major 2D grid, e.g. a puzzle's character map.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    /// Build a grid from text lines, converting every character with `cell`.
+    /// All lines must have the same length.
+    pub fn parse<I, S>(lines: I, mut cell: impl FnMut(char) -> T) -> Result<Self, ParseError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Self::try_parse(lines, |char| Ok(cell(char)))
+    }
+
+    /// Like `parse`, for maps where not every character is allowed.
+    /// The error from `cell` gets the position of the character filled in.
+    pub fn try_parse<I, S>(
+        lines: I,
+        mut cell: impl FnMut(char) -> Result<T, ParseError>,
+    ) -> Result<Self, ParseError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = vec![];
+
+        for line in lines {
+            let line = line.as_ref();
+            let before = cells.len();
+            for (col, char) in line.chars().enumerate() {
+                cells.push(cell(char).map_err(|err| err.at(height + 1, col + 1))?);
+            }
+            let line_width = cells.len() - before;
+
+            match width {
+                None => width = Some(line_width),
+                Some(width) if width != line_width => {
+                    return Err(ParseError::new(line, format!("a row of {width} cells"))
+                        .at(height + 1, width.min(line_width) + 1))
+                }
+                Some(_) => {}
+            }
+            height += 1;
+        }
+
+        Ok(Self {
+            width: width.unwrap_or(0),
+            height,
+            cells,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    fn index_of(&self, Point { row, col }: Point) -> Option<usize> {
+        let in_bounds =
+            (0..self.height as i64).contains(&row) && (0..self.width as i64).contains(&col);
+        in_bounds.then(|| row as usize * self.width + col as usize)
+    }
+
+    fn point_of(&self, index: usize) -> Point {
+        Point::new((index / self.width) as i64, (index % self.width) as i64)
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        self.index_of(point).is_some()
+    }
+
+    pub fn get(&self, point: Point) -> Option<&T> {
+        self.index_of(point).map(|idx| &self.cells[idx])
+    }
+
+    pub fn get_mut(&mut self, point: Point) -> Option<&mut T> {
+        self.index_of(point).map(|idx| &mut self.cells[idx])
+    }
+
+    /// Store `value` and return what was there, or `None` when out of bounds.
+    pub fn replace(&mut self, point: Point, value: T) -> Option<T> {
+        self.get_mut(point)
+            .map(|cell| std::mem::replace(cell, value))
+    }
+
+    /// Every position, row by row.
+    pub fn points(&self) -> impl Iterator<Item = Point> + '_ {
+        (0..self.cells.len()).map(|idx| self.point_of(idx))
+    }
+
+    /// Every cell with its position, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(|(idx, cell)| (self.point_of(idx), cell))
+    }
+
+    fn neighbors<'a>(
+        &'a self,
+        point: Point,
+        directions: &'a [Direction],
+    ) -> impl Iterator<Item = Point> + 'a {
+        directions
+            .iter()
+            .map(move |direction| point + *direction)
+            .filter(|neighbor| self.contains(*neighbor))
+    }
+
+    /// The in-bounds neighbors up, right, down and left of `point`.
+    pub fn neighbors4(&self, point: Point) -> impl Iterator<Item = Point> + '_ {
+        self.neighbors(point, &Direction::CARDINAL)
+    }
+
+    /// Like `neighbors4`, including the diagonals.
+    pub fn neighbors8(&self, point: Point) -> impl Iterator<Item = Point> + '_ {
+        self.neighbors(point, &Direction::ALL)
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[row * self.width..(row + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // `chunks` panics on 0, and an empty grid has no rows anyway:
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn column(&self, col: usize) -> impl Iterator<Item = &T> {
+        self.cells.iter().skip(col).step_by(self.width.max(1))
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(|col| self.column(col))
+    }
+
+    /// The first position (row by row) holding `value`.
+    pub fn find(&self, value: &T) -> Option<Point>
+    where
+        T: PartialEq,
+    {
+        self.cells
+            .iter()
+            .position(|cell| cell == value)
+            .map(|idx| self.point_of(idx))
+    }
+
+    /// Every position holding `value`, row by row.
+    pub fn find_all<'a>(&'a self, value: &'a T) -> impl Iterator<Item = Point> + 'a
+    where
+        T: PartialEq,
+    {
+        self.iter()
+            .filter(move |(_, cell)| *cell == value)
+            .map(|(point, _)| point)
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl Grid<char> {
+    /// Parse the lines as they are, one char per cell.
+    pub fn chars<I, S>(lines: I) -> Result<Self, ParseError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Self::parse(lines, |char| char)
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, point: Point) -> &T {
+        self.get(point)
+            .unwrap_or_else(|| panic!("{point} is out of bounds"))
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, point: Point) -> &mut T {
+        self.get_mut(point)
+            .unwrap_or_else(|| panic!("{point} is out of bounds"))
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+// -- tests --
+
+#[test]
+fn test_parse_and_get() {
+    let grid = Grid::chars(["abc", "def"]).expect("Should be rectangular");
+
+    assert_eq!(grid.width(), 3);
+    assert_eq!(grid.height(), 2);
+    assert_eq!(grid.get(Point::new(1, 2)), Some(&'f'));
+    assert_eq!(grid.get(Point::new(2, 0)), None);
+    assert_eq!(grid.get(Point::new(0, -1)), None);
+    assert_eq!(grid.find(&'e'), Some(Point::new(1, 1)));
+    assert_eq!(grid.to_string(), "abc\ndef\n");
+
+    let err = Grid::chars(["abc", "de"]).expect_err("Should be ragged");
+    assert_eq!((err.line, err.column), (2, 3));
+}
+
+#[test]
+fn test_neighbors_rows_and_columns() {
+    let grid = Grid::parse(["123", "456", "789"], |c| c.to_digit(10).unwrap_or(0)).expect("Ok");
+
+    let corner: Vec<_> = grid.neighbors4(Point::ORIGIN).collect();
+    assert_eq!(corner, vec![Point::new(0, 1), Point::new(1, 0)]);
+    assert_eq!(grid.neighbors8(Point::new(1, 1)).count(), 8);
+    assert_eq!(grid.neighbors8(Point::new(2, 2)).count(), 3);
+
+    assert_eq!(grid.row(1), &[4, 5, 6]);
+    let column: Vec<_> = grid.column(2).copied().collect();
+    assert_eq!(column, vec![3, 6, 9]);
+    assert_eq!(grid.columns().count(), 3);
+}
@@ -0,0 +1,73 @@
+use std::fmt;
+use std::io::{self, Read};
+use std::path::Path;
+
+/// A whole puzzle input, read once up front, so parsers can borrow `&str` lines from it
+/// instead of allocating a `String` per line, and I/O errors show up before parsing starts.
+pub struct PuzzleInput {
+    text: String,
+}
+
+impl PuzzleInput {
+    /// Read a file into memory.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self::from(std::fs::read_to_string(path)?))
+    }
+
+    /// Read everything from stdin, e.g. `aoc run --input -` with the input piped in.
+    pub fn stdin() -> io::Result<Self> {
+        let mut text = String::new();
+        io::stdin().read_to_string(&mut text)?;
+        Ok(Self::from(text))
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The lines, borrowed from the input, without their `\n` or `\r\n`.
+    pub fn lines(&self) -> std::str::Lines<'_> {
+        self.text().lines()
+    }
+}
+
+/// An in-memory input, e.g. an example pasted into a test.
+impl From<String> for PuzzleInput {
+    fn from(text: String) -> Self {
+        Self { text }
+    }
+}
+
+impl From<&str> for PuzzleInput {
+    fn from(text: &str) -> Self {
+        Self::from(text.to_string())
+    }
+}
+
+impl fmt::Debug for PuzzleInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PuzzleInput")
+            .field("len", &self.text().len())
+            .finish()
+    }
+}
+
+// -- tests --
+
+#[test]
+fn test_input_sources() {
+    let inline = PuzzleInput::from("3   4\r\n4   3\n\n");
+    let lines: Vec<&str> = inline.lines().collect();
+    assert_eq!(lines, vec!["3   4", "4   3", ""]);
+
+    let path = std::env::temp_dir().join(format!("aoc_input_{}.txt", std::process::id()));
+    std::fs::write(&path, "MMMSXXMASM\nMSAMXMSMSA\n").expect("Temp dir");
+
+    let read = PuzzleInput::open(&path).expect("Readable");
+    assert_eq!(read.lines().nth(1), Some("MSAMXMSMSA"));
+
+    std::fs::write(&path, [0xff, 0xfe]).expect("Temp dir");
+    assert!(PuzzleInput::open(&path).is_err());
+
+    let _ = std::fs::remove_file(&path);
+}
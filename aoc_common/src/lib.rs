@@ -0,0 +1,26 @@
+//! Shared plumbing for the day_N crates: input loading and downloading, recorded answers, result types, parse errors, the `Solution` trait and its benchmarks, grids and geometry.
+
+mod answer;
+mod bench;
+pub mod geometry;
+pub mod grid;
+mod input;
+mod parse_error;
+mod provider;
+mod registry;
+mod solution;
+
+pub use answer::Answer;
+pub use bench::{bench, DayBench, Stats, STAGES};
+pub use geometry::{Direction, Point};
+pub use grid::Grid;
+pub use input::PuzzleInput;
+pub use parse_error::{parse_in, ParseError};
+pub use provider::{
+    workspace_dir, Fetcher, HttpFetcher, InputProvider, DEFAULT_BASE_URL, REAL_INPUT, YEAR,
+};
+pub use registry::{AnswerRegistry, PartAnswers, VariantAnswers, Verdict, ANSWERS_FILE};
+pub use solution::{Explain, Format, Solution};
+
+/// What the `simple` / `advanced` solvers return; most puzzles have a numeric answer.
+pub type AocResult<T = i64> = anyhow::Result<T>;
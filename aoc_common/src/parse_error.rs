@@ -0,0 +1,87 @@
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+/// A malformed bit of puzzle input: where it was, what it said and what should have been there.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based; 0 if the parser only saw a single line and the caller hasn't filled it in.
+    pub line: usize,
+    /// 1-based, in characters.
+    pub column: usize,
+    pub text: String,
+    pub expected: String,
+}
+
+impl ParseError {
+    pub fn new(text: impl Into<String>, expected: impl Into<String>) -> Self {
+        Self {
+            line: 0,
+            column: 1,
+            text: text.into(),
+            expected: expected.into(),
+        }
+    }
+
+    /// `text` should be a slice of `line` (e.g. from `split` or `trim`), so its column can be worked out.
+    pub fn in_line(line: &str, text: &str, expected: impl Into<String>) -> Self {
+        let offset = (text.as_ptr() as usize).wrapping_sub(line.as_ptr() as usize);
+        let column = match line.get(..offset) {
+            Some(before) if offset + text.len() <= line.len() => before.chars().count() + 1,
+            _ => 1,
+        };
+
+        Self {
+            column,
+            ..Self::new(text, expected)
+        }
+    }
+
+    pub fn at(self, line: usize, column: usize) -> Self {
+        Self {
+            line,
+            column,
+            ..self
+        }
+    }
+
+    /// Fill in the line number, for errors from a parser that only saw that one line.
+    pub fn on_line(self, line: usize) -> Self {
+        Self { line, ..self }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.line > 0 {
+            write!(f, "line {}, column {}: ", self.line, self.column)?;
+        }
+        write!(f, "expected {}, found {:?}", self.expected, self.text)
+    }
+}
+
+impl Error for ParseError {}
+
+/// Parse `text` (a slice of `line`) with `FromStr`, e.g. a number between separators.
+pub fn parse_in<T: FromStr>(line: &str, text: &str, expected: &str) -> Result<T, ParseError> {
+    text.parse()
+        .map_err(|_| ParseError::in_line(line, text, expected))
+}
+
+// -- tests --
+
+#[test]
+fn test_parse_error_location() {
+    let line = "47|5x";
+    let (_, right) = line.split_once('|').expect("Has a pipe");
+
+    let err = parse_in::<i64>(line, right, "a page number").expect_err("Not a number");
+    assert_eq!(err.column, 4);
+    assert_eq!(
+        err.on_line(3).to_string(),
+        r#"line 3, column 4: expected a page number, found "5x""#
+    );
+
+    // text that isn't part of the line can't be located:
+    assert_eq!(ParseError::in_line(line, "5x", "").column, 1);
+}
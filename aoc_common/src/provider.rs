@@ -0,0 +1,262 @@
+use crate::{AocResult, PuzzleInput};
+use anyhow::Context;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The calendar these days belong to.
+pub const YEAR: u16 = 2024;
+
+/// The variant holding your personal puzzle input; the only one that can be downloaded.
+pub const REAL_INPUT: &str = "input";
+
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+
+/// Downloads puzzle inputs, so tests can swap the real site for something local.
+pub trait Fetcher {
+    fn fetch(&self, url: &str, session: &str) -> AocResult<String>;
+}
+
+/// Fetches over HTTP(S) with the session cookie, like a logged-in browser would.
+pub struct HttpFetcher;
+
+impl Fetcher for HttpFetcher {
+    fn fetch(&self, url: &str, session: &str) -> AocResult<String> {
+        let body = ureq::get(url)
+            .set("Cookie", &format!("session={session}"))
+            .set(
+                "User-Agent",
+                "github.com/robinvandernoord/advent-of-code-2024",
+            )
+            .call()?
+            .into_string()?;
+
+        Ok(body)
+    }
+}
+
+/// The root of this repository, which holds `inputs/`.
+pub fn workspace_dir() -> PathBuf {
+    let aoc_common = Path::new(env!("CARGO_MANIFEST_DIR"));
+    aoc_common.parent().unwrap_or(aoc_common).to_path_buf()
+}
+
+/// Finds the input file for a (year, day, variant), e.g. (2024, 7, "example"),
+/// under `<cache_dir>/<year>/day_<day>/<variant>.txt`.
+///
+/// Examples are checked in; personal inputs aren't, so a missing `input` is downloaded
+/// when a session token is configured.
+pub struct InputProvider {
+    cache_dir: PathBuf,
+    base_url: String,
+    session: Option<String>,
+    fetcher: Box<dyn Fetcher>,
+}
+
+impl InputProvider {
+    pub fn new(cache_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            cache_dir: cache_dir.into(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            session: None,
+            fetcher: Box::new(HttpFetcher),
+        }
+    }
+
+    /// The workspace's `inputs/` directory, overridable like the rest with
+    /// `AOC_CACHE_DIR`, `AOC_BASE_URL` and `AOC_SESSION`.
+    pub fn from_env() -> Self {
+        let cache_dir = std::env::var("AOC_CACHE_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| workspace_dir().join("inputs"));
+
+        let mut provider = Self::new(cache_dir);
+        if let Ok(base_url) = std::env::var("AOC_BASE_URL") {
+            provider = provider.with_base_url(base_url);
+        }
+        if let Ok(session) = std::env::var("AOC_SESSION") {
+            provider = provider.with_session(session.trim());
+        }
+        provider
+    }
+
+    pub fn with_base_url(self, base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into(),
+            ..self
+        }
+    }
+
+    pub fn with_session(self, session: impl Into<String>) -> Self {
+        Self {
+            session: Some(session.into()),
+            ..self
+        }
+    }
+
+    pub fn with_fetcher(self, fetcher: impl Fetcher + 'static) -> Self {
+        Self {
+            fetcher: Box::new(fetcher),
+            ..self
+        }
+    }
+
+    /// The directory with all of a day's inputs (and its answers).
+    pub fn day_dir(&self, year: u16, day: u8) -> PathBuf {
+        self.cache_dir
+            .join(year.to_string())
+            .join(format!("day_{day}"))
+    }
+
+    /// Where this input lives in the cache, whether it's there or not.
+    pub fn path(&self, year: u16, day: u8, variant: &str) -> PathBuf {
+        self.day_dir(year, day).join(format!("{variant}.txt"))
+    }
+
+    /// The path of the input, downloading it first if needed (and possible).
+    pub fn resolve(&self, year: u16, day: u8, variant: &str) -> AocResult<PathBuf> {
+        let path = self.path(year, day, variant);
+        if path.exists() {
+            return Ok(path);
+        }
+
+        let session = match &self.session {
+            Some(session) if variant == REAL_INPUT => session,
+            _ => anyhow::bail!("can't read {}", path.display()),
+        };
+
+        let url = format!(
+            "{}/{year}/day/{day}/input",
+            self.base_url.trim_end_matches('/')
+        );
+        let body = self
+            .fetcher
+            .fetch(&url, session)
+            .with_context(|| format!("Can't download {url}"))?;
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&path, body).with_context(|| format!("Can't write {}", path.display()))?;
+
+        Ok(path)
+    }
+
+    pub fn open(&self, year: u16, day: u8, variant: &str) -> AocResult<PuzzleInput> {
+        let path = self.resolve(year, day, variant)?;
+
+        PuzzleInput::open(&path).with_context(|| format!("Can't read {}", path.display()))
+    }
+}
+
+/// Open one of a `Solution`'s inputs for a test, or return early when it's the personal input and it isn't there.
+///
+/// Puzzle inputs are personal and not checked in, so a fresh checkout only has
+/// the examples; `cargo test --workspace` should still run everything else.
+/// The examples are checked in, so a missing one fails the test instead of skipping it.
+#[macro_export]
+macro_rules! input_or_skip {
+    ($solution:ty, $variant:expr) => {{
+        let variant = $variant;
+        match $crate::InputProvider::from_env().open(
+            $crate::YEAR,
+            <$solution as $crate::Solution>::DAY,
+            variant,
+        ) {
+            Ok(input) => input,
+            Err(err) if variant == $crate::REAL_INPUT => {
+                eprintln!("skipping: {err:#}");
+                return;
+            }
+            Err(err) => panic!("The {variant} input should be checked in: {err:#}"),
+        }
+    }};
+}
+
+// -- tests --
+
+#[cfg(test)]
+fn temp_cache_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("aoc_{name}_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn test_provider_without_session() {
+    struct Unreachable;
+
+    impl Fetcher for Unreachable {
+        fn fetch(&self, url: &str, _session: &str) -> AocResult<String> {
+            panic!("Shouldn't download {url}")
+        }
+    }
+
+    let cache_dir = temp_cache_dir("offline");
+    let provider = InputProvider::new(&cache_dir).with_fetcher(Unreachable);
+
+    let path = provider.path(2024, 7, "example");
+    assert_eq!(path, cache_dir.join("2024/day_7/example.txt"));
+    assert!(provider.resolve(2024, 7, "example").is_err());
+    assert!(provider.resolve(2024, 7, REAL_INPUT).is_err());
+
+    // examples are never downloaded, even with a session:
+    let provider = provider.with_session("secret");
+    assert!(provider.resolve(2024, 7, "example").is_err());
+
+    fs::create_dir_all(path.parent().expect("Has a parent")).expect("Temp dir");
+    fs::write(&path, "190: 10 19\n").expect("Temp dir");
+    assert_eq!(provider.resolve(2024, 7, "example").expect("Cached"), path);
+
+    let _ = fs::remove_dir_all(&cache_dir);
+}
+
+#[test]
+fn test_provider_downloads_from_mock_server() {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Free port");
+    let base_url = format!("http://{}", listener.local_addr().expect("Bound"));
+
+    // answers exactly one request, and hands back its head for inspection:
+    let server = std::thread::spawn(move || {
+        let (stream, _) = listener.accept().expect("A request");
+        let mut reader = BufReader::new(stream);
+        let mut head = vec![];
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).expect("Readable request");
+            if line.trim().is_empty() {
+                break;
+            }
+            head.push(line.trim().to_string());
+        }
+
+        let body = "1 2\n3 4\n";
+        let mut stream = reader.into_inner();
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+        .expect("Writable response");
+        head
+    });
+
+    let cache_dir = temp_cache_dir("download");
+    let provider = InputProvider::new(&cache_dir)
+        .with_base_url(base_url)
+        .with_session("secret");
+
+    let path = provider.resolve(2024, 1, REAL_INPUT).expect("Downloaded");
+    assert_eq!(fs::read_to_string(&path).expect("Cached"), "1 2\n3 4\n");
+
+    let head = server.join().expect("Mock server");
+    assert_eq!(head[0], "GET /2024/day/1/input HTTP/1.1");
+    assert!(head.iter().any(|line| line == "Cookie: session=secret"));
+
+    // the server is gone now, so this has to come from the cache:
+    assert_eq!(provider.resolve(2024, 1, REAL_INPUT).expect("Cached"), path);
+
+    let _ = fs::remove_dir_all(&cache_dir);
+}
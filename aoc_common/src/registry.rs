@@ -0,0 +1,251 @@
+use crate::{Answer, AocResult};
+use anyhow::Context;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt::{self, Display};
+use std::path::Path;
+
+/// Name of the answers file, next to a day's inputs.
+pub const ANSWERS_FILE: &str = "answers.toml";
+
+/// Everything known about a day's answers, per input variant:
+///
+/// ```toml
+/// [example]
+/// part1 = 18
+/// part2 = 9
+///
+/// [input]
+/// part1 = 2390
+/// part2 = { answer = 1809, too_low = [441], too_high = [1840] }
+/// ```
+///
+/// A part is either the plain answer, or a table with the `answer` (if known yet) and the
+/// submissions the site rejected: `too_low`, `too_high` or just `wrong`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct AnswerRegistry {
+    pub variants: BTreeMap<String, VariantAnswers>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VariantAnswers {
+    pub part1: Option<PartAnswers>,
+    pub part2: Option<PartAnswers>,
+}
+
+impl VariantAnswers {
+    pub fn part(&self, part: u8) -> Option<&PartAnswers> {
+        match part {
+            1 => self.part1.as_ref(),
+            2 => self.part2.as_ref(),
+            _ => None,
+        }
+    }
+}
+
+/// An answer as written in the file: a number or a string.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+enum Recorded {
+    Int(i64),
+    Str(String),
+}
+
+impl From<Recorded> for Answer {
+    fn from(recorded: Recorded) -> Self {
+        match recorded {
+            Recorded::Int(number) => Answer::Int(number),
+            Recorded::Str(string) => Answer::Str(string),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawPart {
+    Plain(Recorded),
+    Table {
+        answer: Option<Recorded>,
+        #[serde(default)]
+        too_low: Vec<i64>,
+        #[serde(default)]
+        too_high: Vec<i64>,
+        #[serde(default)]
+        wrong: Vec<Recorded>,
+    },
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(from = "RawPart")]
+pub struct PartAnswers {
+    pub answer: Option<Answer>,
+    /// The highest submission that was too low, so the answer is above it.
+    pub too_low: Option<i64>,
+    /// The lowest submission that was too high, so the answer is below it.
+    pub too_high: Option<i64>,
+    /// Every rejected submission, including the too low/high ones.
+    pub wrong: Vec<Answer>,
+}
+
+impl From<RawPart> for PartAnswers {
+    fn from(raw: RawPart) -> Self {
+        match raw {
+            RawPart::Plain(answer) => Self {
+                answer: Some(answer.into()),
+                ..Default::default()
+            },
+            RawPart::Table {
+                answer,
+                too_low,
+                too_high,
+                wrong,
+            } => Self {
+                answer: answer.map(Answer::from),
+                too_low: too_low.iter().copied().max(),
+                too_high: too_high.iter().copied().min(),
+                wrong: too_low
+                    .into_iter()
+                    .chain(too_high)
+                    .map(Answer::Int)
+                    .chain(wrong.into_iter().map(Answer::from))
+                    .collect(),
+            },
+        }
+    }
+}
+
+/// How a computed answer compares to what's recorded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    /// Same as the recorded answer.
+    Pass,
+    /// There's a recorded answer, and this isn't it (anymore).
+    Regression { expected: Answer },
+    /// No answer recorded yet, but this one was already rejected or is out of bounds.
+    Fail { reason: String },
+    /// No answer recorded yet, and it's not ruled out by earlier rejected submissions.
+    WithinBounds,
+    /// Nothing recorded to compare against.
+    Unknown,
+    /// The solver doesn't give an answer for this part.
+    Unsolved,
+}
+
+impl Verdict {
+    pub fn is_ok(&self) -> bool {
+        !matches!(self, Self::Regression { .. } | Self::Fail { .. })
+    }
+}
+
+impl Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pass => write!(f, "pass"),
+            Self::Regression { expected } => write!(f, "regression (expected {expected})"),
+            Self::Fail { reason } => write!(f, "fail ({reason})"),
+            Self::WithinBounds => write!(f, "within known bounds"),
+            Self::Unknown => write!(f, "unknown"),
+            Self::Unsolved => write!(f, "unsolved"),
+        }
+    }
+}
+
+impl PartAnswers {
+    pub fn check(&self, answer: &Answer) -> Verdict {
+        if answer == &Answer::Unsolved {
+            return Verdict::Unsolved;
+        }
+
+        if let Some(expected) = &self.answer {
+            return if expected == answer {
+                Verdict::Pass
+            } else {
+                Verdict::Regression {
+                    expected: expected.clone(),
+                }
+            };
+        }
+
+        let number = answer.as_int();
+        if let (Some(number), Some(too_low)) = (number, self.too_low) {
+            if number <= too_low {
+                let reason = format!("{too_low} was already too low");
+                return Verdict::Fail { reason };
+            }
+        }
+        if let (Some(number), Some(too_high)) = (number, self.too_high) {
+            if number >= too_high {
+                let reason = format!("{too_high} was already too high");
+                return Verdict::Fail { reason };
+            }
+        }
+        if self.wrong.contains(answer) {
+            let reason = "already submitted and wrong".to_string();
+            return Verdict::Fail { reason };
+        }
+
+        if self.wrong.is_empty() {
+            Verdict::Unknown
+        } else {
+            Verdict::WithinBounds
+        }
+    }
+}
+
+impl AnswerRegistry {
+    pub fn parse(text: &str) -> AocResult<Self> {
+        Ok(toml::from_str(text)?)
+    }
+
+    pub fn load(path: &Path) -> AocResult<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Can't read {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("Invalid answers in {}", path.display()))
+    }
+
+    pub fn get(&self, variant: &str, part: u8) -> Option<&PartAnswers> {
+        self.variants.get(variant)?.part(part)
+    }
+}
+
+// -- tests --
+
+#[test]
+fn test_registry_verdicts() {
+    let registry = AnswerRegistry::parse(
+        r#"
+        [example]
+        part1 = "4,6,3"
+
+        [input]
+        part1 = 2390
+        part2 = { too_low = [400, 441], too_high = [1840], wrong = [1000] }
+        "#,
+    )
+    .expect("Valid toml");
+
+    let example = registry.get("example", 1).expect("Recorded");
+    assert_eq!(example.check(&"4,6,3".to_string().into()), Verdict::Pass);
+
+    let part1 = registry.get("input", 1).expect("Recorded");
+    assert_eq!(part1.check(&2390.into()), Verdict::Pass);
+    assert_eq!(
+        part1.check(&2391.into()),
+        Verdict::Regression {
+            expected: 2390.into()
+        }
+    );
+    assert_eq!(part1.check(&Answer::Unsolved), Verdict::Unsolved);
+
+    let part2 = registry.get("input", 2).expect("Recorded");
+    assert_eq!(part2.too_low, Some(441));
+    assert!(!part2.check(&441.into()).is_ok());
+    assert!(!part2.check(&1840.into()).is_ok());
+    assert!(!part2.check(&1000.into()).is_ok());
+    assert_eq!(part2.check(&1809.into()), Verdict::WithinBounds);
+
+    assert!(registry.get("example", 2).is_none());
+    assert!(AnswerRegistry::parse("[input]\npart3 = 1").is_err());
+}
@@ -0,0 +1,103 @@
+use crate::{Answer, AocResult, PuzzleInput};
+use std::fmt::{self, Display};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// How `Solution::explain` writes its report.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Format {
+    /// For people.
+    #[default]
+    Text,
+    /// For other tools; the exact shape is up to each day.
+    Json,
+    /// A Graphviz graph, for the days that have one.
+    Dot,
+}
+
+impl FromStr for Format {
+    type Err = anyhow::Error;
+
+    fn from_str(format: &str) -> AocResult<Self> {
+        match format {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            "dot" => Ok(Self::Dot),
+            other => anyhow::bail!("Unknown format {other:?}, expected `text`, `json` or `dot`"),
+        }
+    }
+}
+
+impl Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Text => write!(f, "text"),
+            Self::Json => write!(f, "json"),
+            Self::Dot => write!(f, "dot"),
+        }
+    }
+}
+
+/// What `Solution::explain` reports, and how; each day uses the options that apply to it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Explain {
+    pub format: Format,
+    /// Color text reports with ANSI escapes, for a terminal.
+    pub color: bool,
+    /// Also draw the report as a PNG, for the days that can.
+    pub png: Option<PathBuf>,
+    /// Single out one (1-based) item of the input, like one of day 5's updates.
+    pub focus: Option<usize>,
+    /// Leave out everything but `focus` where the report would show more, like day 5's other rules.
+    pub only_focus: bool,
+}
+
+impl From<Format> for Explain {
+    fn from(format: Format) -> Self {
+        Self {
+            format,
+            ..Default::default()
+        }
+    }
+}
+
+/// One day of the calendar, so the runner and tests can treat every day the same way.
+pub trait Solution {
+    const DAY: u8;
+
+    /// The parsed puzzle input, shared by both parts.
+    type Input;
+
+    /// Puzzle parameters that aren't part of the input (grid sizes, iteration counts, ...).
+    /// `Default` should give the values for the real input.
+    type Config: Default;
+
+    /// Parts that don't compute an answer (yet) and may do something else instead, like writing images
+    /// to look through by hand; `bench` leaves them out.
+    const UNSOLVED: &'static [u8] = &[];
+
+    fn parse(input: &PuzzleInput) -> AocResult<Self::Input>;
+
+    fn part1(input: &Self::Input, config: &Self::Config) -> AocResult<Answer>;
+    fn part2(input: &Self::Input, config: &Self::Config) -> AocResult<Answer>;
+
+    fn solve_with(part: u8, input: &PuzzleInput, config: &Self::Config) -> AocResult<Answer> {
+        let input = Self::parse(input)?;
+
+        match part {
+            1 => Self::part1(&input, config),
+            2 => Self::part2(&input, config),
+            other => anyhow::bail!("Day {} has no part {other}", Self::DAY),
+        }
+    }
+
+    fn solve(part: u8, input: &PuzzleInput) -> AocResult<Answer> {
+        Self::solve_with(part, input, &Self::Config::default())
+    }
+
+    /// A closer look at how the answers come about, for the days that have one.
+    fn explain(input: &Self::Input, config: &Self::Config, options: &Explain) -> AocResult<String> {
+        let _ = (input, config, options);
+        anyhow::bail!("Day {} has nothing to explain", Self::DAY)
+    }
+}
@@ -1,71 +1,75 @@
 #![allow(dead_code)]
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
+use aoc_common::{Answer, AocResult, PuzzleInput, Solution};
 
-async fn simple(file: FileHandle) -> anyhow::Result<i64> {
-    for line in file.map_while(Result::ok) {
+fn simple(lines: &[String]) -> AocResult {
+    for line in lines {
         println!("{}", line);
     }
     Ok(0)
 }
 
-async fn advanced(file: FileHandle) -> anyhow::Result<i64> {
-    for line in file.map_while(Result::ok) {
+fn advanced(lines: &[String]) -> AocResult {
+    for line in lines {
         println!("{}", line);
     }
     Ok(0)
 }
 
-// -- tests --
+pub struct Day0;
+
+impl Solution for Day0 {
+    const DAY: u8 = 0;
 
-type FileHandle = io::Lines<io::BufReader<File>>;
+    type Input = Vec<String>;
+    type Config = ();
 
-async fn read_lines<P: AsRef<Path>>(filename: P) -> io::Result<FileHandle> {
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
+    fn parse(input: &PuzzleInput) -> AocResult<Self::Input> {
+        Ok(input.lines().map(String::from).collect())
+    }
+
+    fn part1(input: &Self::Input, _config: &()) -> AocResult<Answer> {
+        Ok(simple(input)?.into())
+    }
+
+    fn part2(input: &Self::Input, _config: &()) -> AocResult<Answer> {
+        Ok(advanced(input)?.into())
+    }
 }
 
-#[tokio::test]
-async fn test_simple_minimal() {
+// -- tests --
+
+#[test]
+fn test_simple_minimal() {
     let answer = 0;
 
-    let file = read_lines("minimal.txt")
-        .await
-        .expect("Should be able to read minimal.txt");
+    let file = aoc_common::input_or_skip!(Day0, "example");
 
-    assert_eq!(simple(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day0::solve(1, &file).expect("Oof 1"), answer);
 }
 
-// #[tokio::test]
-// async fn test_simple() {
+// #[test]
+// fn test_simple() {
 //     let answer = 0;
 //
-//     let file = read_lines("input.txt")
-//         .await
-//         .expect("Should be able to read input.txt");
+//     let file = aoc_common::input_or_skip!(Day0, "input");
 //
-//     assert_eq!(simple(file).await.expect("Oof 1"), answer);
+//     assert_eq!(Day0::solve(1, &file).expect("Oof 1"), answer);
 // }
 //
-// #[tokio::test]
-// async fn test_advanced_minimal() {
+// #[test]
+// fn test_advanced_minimal() {
 //     let answer = 0;
 //
-//     let file = read_lines("minimal.txt")
-//         .await
-//         .expect("Should be able to read minimal.txt");
+//     let file = aoc_common::input_or_skip!(Day0, "example");
 //
-//     assert_eq!(advanced(file).await.expect("Oof 1"), answer);
+//     assert_eq!(Day0::solve(2, &file).expect("Oof 1"), answer);
 // }
 //
-// #[tokio::test]
-// async fn test_advanced() {
+// #[test]
+// fn test_advanced() {
 //     let answer = 0;
 //
-//     let file = read_lines("input.txt")
-//         .await
-//         .expect("Should be able to read input.txt");
+//     let file = aoc_common::input_or_skip!(Day0, "input");
 //
-//     assert_eq!(advanced(file).await.expect("Oof 2"), answer);
+//     assert_eq!(Day0::solve(2, &file).expect("Oof 2"), answer);
 // }
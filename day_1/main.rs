@@ -1,104 +1,518 @@
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
+#![allow(dead_code)]
 
-fn parse_lines(file: FileHandle) -> anyhow::Result<(Vec<i64>, Vec<i64>)> {
-    let mut left = vec![];
-    let mut right = vec![];
+use aoc_common::{parse_in, Answer, AocResult, Explain, Format, ParseError, PuzzleInput, Solution};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::{self, Display};
 
-    for line in file.flatten() {
-        let parts: Vec<_> = line.split(" ").collect();
-        let left_str = parts.first().expect("Should have");
-        let right_str = parts.last().expect("Should have");
+/// The input's lists, one per column.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Columns {
+    lists: Vec<Vec<i64>>,
+}
+
+impl Columns {
+    pub fn count(&self) -> usize {
+        self.lists.len()
+    }
+
+    pub fn column(&self, index: usize) -> Option<&[i64]> {
+        self.lists.get(index).map(Vec::as_slice)
+    }
+
+    /// Two columns to compare, e.g. (0, 1) for the puzzle's left and right list.
+    pub fn pair(&self, (left, right): (usize, usize)) -> AocResult<(&[i64], &[i64])> {
+        match (self.column(left), self.column(right)) {
+            (Some(left), Some(right)) => Ok((left, right)),
+            _ => anyhow::bail!(
+                "Can't compare columns {left} and {right}, there are only {}",
+                self.count()
+            ),
+        }
+    }
+}
+
+/// Parse one row of whitespace-separated (spaces or tabs) numbers into `row`, which is reused between rows.
+fn parse_row(line: &str, row: &mut Vec<i64>) -> Result<(), ParseError> {
+    row.clear();
+    for number in line.split_whitespace() {
+        row.push(parse_in(line, number, "a number")?);
+    }
+
+    Ok(())
+}
+
+/// Every row has to have as many numbers as the first.
+fn check_width(line: &str, row: &[i64], width: usize) -> Result<(), ParseError> {
+    if row.len() == width {
+        return Ok(());
+    }
 
-        let left_i: i64 = left_str.parse()?;
-        let right_i: i64 = right_str.parse()?;
+    let expected = format!("{width} numbers per row, not {}", row.len());
+    let extra = line.split_whitespace().nth(width).unwrap_or(line);
+    Err(ParseError::in_line(line, extra, expected))
+}
+
+/// Parse any number of whitespace-separated columns into one list per column.
+pub fn parse_columns(input: &PuzzleInput) -> Result<Columns, ParseError> {
+    let mut lists: Vec<Vec<i64>> = vec![];
+    let mut row = vec![];
+
+    for (idx, line) in input.lines().enumerate() {
+        parse_row(line, &mut row).map_err(|err| err.on_line(idx + 1))?;
+
+        if idx == 0 {
+            lists = vec![vec![]; row.len()];
+        }
+        check_width(line, &row, lists.len()).map_err(|err| err.on_line(idx + 1))?;
 
-        left.push(left_i);
-        right.push(right_i);
+        for (list, &number) in lists.iter_mut().zip(&row) {
+            list.push(number);
+        }
     }
 
-    Ok((left, right))
+    Ok(Columns { lists })
 }
 
-async fn simple(file: FileHandle) -> anyhow::Result<i64> {
-    let (mut left, mut right) = parse_lines(file)?;
+/// Pair up the smallest values of both lists, then the second smallest, etc. and add up how far apart they are.
+pub fn total_distance(left: &[i64], right: &[i64]) -> i64 {
+    let mut left = left.to_vec();
+    let mut right = right.to_vec();
 
     left.sort_unstable();
-    // left.dedup();
+    right.sort_unstable();
+
+    left.iter().zip(right).map(|(l, r)| (l - r).abs()).sum()
+}
+
+/// How often every value occurs in a list.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Counts {
+    counts: HashMap<i64, i64>,
+}
+
+impl Counts {
+    pub fn add(&mut self, value: i64) {
+        *self.counts.entry(value).or_insert(0) += 1;
+    }
+
+    pub fn get(&self, value: i64) -> i64 {
+        self.counts.get(&value).copied().unwrap_or(0)
+    }
+
+    /// (value, count) in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (i64, i64)> + '_ {
+        self.counts.iter().map(|(&value, &count)| (value, count))
+    }
+}
+
+impl FromIterator<i64> for Counts {
+    fn from_iter<I: IntoIterator<Item = i64>>(values: I) -> Self {
+        let mut counts = Self::default();
+        for value in values {
+            counts.add(value);
+        }
+        counts
+    }
+}
+
+/// Every value on the left, times how often it appears on the right.
+pub fn similarity_score(left: &[i64], right: &[i64]) -> i64 {
+    let left_counts: Counts = left.iter().copied().collect();
+    let right_counts: Counts = right.iter().copied().collect();
+
+    similarity_of_counts(&left_counts, &right_counts)
+}
+
+/// `similarity_score`, for lists that were only counted.
+pub fn similarity_of_counts(left: &Counts, right: &Counts) -> i64 {
+    left.iter()
+        .map(|(value, count)| value * count * right.get(value))
+        .sum()
+}
+
+/// Count two columns of the input without collecting them, so a huge input only costs memory per distinct value.
+/// That's enough for the similarity score (though not for the distance, which needs everything sorted).
+pub fn count_columns(
+    input: &PuzzleInput,
+    (left, right): (usize, usize),
+) -> Result<(Counts, Counts), ParseError> {
+    let mut left_counts = Counts::default();
+    let mut right_counts = Counts::default();
+    let mut row = vec![];
+    let mut width = None;
+
+    for (idx, line) in input.lines().enumerate() {
+        parse_row(line, &mut row).map_err(|err| err.on_line(idx + 1))?;
+
+        let width = *width.get_or_insert(row.len());
+        check_width(line, &row, width).map_err(|err| err.on_line(idx + 1))?;
+
+        match (row.get(left), row.get(right)) {
+            (Some(&l), Some(&r)) => {
+                left_counts.add(l);
+                right_counts.add(r);
+            }
+            _ => {
+                let expected = format!("columns {left} and {right}");
+                return Err(ParseError::in_line(line, line, expected).on_line(idx + 1));
+            }
+        }
+    }
+
+    Ok((left_counts, right_counts))
+}
+
+/// Distinct values on both sides, divided by the distinct values on either side: 1.0 for the same sets of values.
+pub fn jaccard_index(left: &[i64], right: &[i64]) -> f64 {
+    let left: BTreeSet<i64> = left.iter().copied().collect();
+    let right: BTreeSet<i64> = right.iter().copied().collect();
+
+    let union = left.union(&right).count();
+    if union == 0 {
+        return 1.0;
+    }
+    left.intersection(&right).count() as f64 / union as f64
+}
+
+/// How many values can be matched one-to-one with an equal value in the other list.
+pub fn multiset_intersection(left: &[i64], right: &[i64]) -> i64 {
+    let left_counts: Counts = left.iter().copied().collect();
+    let right_counts: Counts = right.iter().copied().collect();
+
+    left_counts
+        .iter()
+        .map(|(value, count)| count.min(right_counts.get(value)))
+        .sum()
+}
+
+/// What a single value adds to the similarity score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Contribution {
+    pub value: i64,
+    pub left_count: i64,
+    pub right_count: i64,
+    /// `value * left_count * right_count`
+    pub score: i64,
+}
+
+/// Every distinct value on the left, biggest contributions first.
+pub fn contributions(left: &[i64], right: &[i64]) -> Vec<Contribution> {
+    let left_counts: Counts = left.iter().copied().collect();
+    let right_counts: Counts = right.iter().copied().collect();
+
+    let mut contributions: Vec<Contribution> = left_counts
+        .iter()
+        .map(|(value, left_count)| {
+            let right_count = right_counts.get(value);
+            Contribution {
+                value,
+                left_count,
+                right_count,
+                score: value * left_count * right_count,
+            }
+        })
+        .collect();
 
+    contributions.sort_by_key(|it| (std::cmp::Reverse(it.score), it.value));
+    contributions
+}
+
+/// The distinct values of `list` that never appear in `other`, in order.
+pub fn only_in(list: &[i64], other: &[i64]) -> Vec<i64> {
+    let other: BTreeSet<i64> = other.iter().copied().collect();
+    let list: BTreeSet<i64> = list.iter().copied().collect();
+
+    list.difference(&other).copied().collect()
+}
+
+/// One pair from the distance calculation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Discrepancy {
+    /// 0 for the smallest values of both lists, 1 for the second smallest, etc.
+    pub rank: usize,
+    pub left: i64,
+    pub right: i64,
+    pub distance: i64,
+}
+
+/// The `n` pairs of sorted values that are furthest apart, furthest first.
+pub fn top_discrepancies(left: &[i64], right: &[i64], n: usize) -> Vec<Discrepancy> {
+    let mut left = left.to_vec();
+    let mut right = right.to_vec();
+
+    left.sort_unstable();
     right.sort_unstable();
-    // right.dedup();
 
-    let mut result = 0;
-    for (l, r) in left.iter().zip(right) {
-        let delta = (l - r).abs();
-        result += delta;
+    let mut pairs: Vec<Discrepancy> = left
+        .into_iter()
+        .zip(right)
+        .enumerate()
+        .map(|(rank, (left, right))| Discrepancy {
+            rank,
+            left,
+            right,
+            distance: (left - right).abs(),
+        })
+        .collect();
+
+    pairs.sort_by_key(|it| (std::cmp::Reverse(it.distance), it.rank));
+    pairs.truncate(n);
+    pairs
+}
+
+/// Everything there is to know about how two lists compare, for reconciling them.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Report {
+    pub distance: i64,
+    pub similarity: i64,
+    pub jaccard: f64,
+    pub multiset_intersection: i64,
+    pub contributions: Vec<Contribution>,
+    pub only_left: Vec<i64>,
+    pub only_right: Vec<i64>,
+    pub discrepancies: Vec<Discrepancy>,
+}
+
+impl Report {
+    pub fn new(left: &[i64], right: &[i64], top: usize) -> Self {
+        Self {
+            distance: total_distance(left, right),
+            similarity: similarity_score(left, right),
+            jaccard: jaccard_index(left, right),
+            multiset_intersection: multiset_intersection(left, right),
+            contributions: contributions(left, right),
+            only_left: only_in(left, right),
+            only_right: only_in(right, left),
+            discrepancies: top_discrepancies(left, right, top),
+        }
     }
+}
+
+fn write_values(f: &mut fmt::Formatter<'_>, label: &str, values: &[i64]) -> fmt::Result {
+    let values: Vec<String> = values.iter().map(i64::to_string).collect();
+    writeln!(f, "{label} ({}): {}", values.len(), values.join(", "))
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "distance: {}", self.distance)?;
+        writeln!(f, "similarity: {}", self.similarity)?;
+        writeln!(f, "jaccard index: {:.3}", self.jaccard)?;
+        writeln!(f, "multiset intersection: {}", self.multiset_intersection)?;
+
+        writeln!(f, "similarity per value:")?;
+        for it in self.contributions.iter().filter(|it| it.score != 0) {
+            writeln!(
+                f,
+                "  {} ({}x left, {}x right): {}",
+                it.value, it.left_count, it.right_count, it.score
+            )?;
+        }
+
+        write_values(f, "only left", &self.only_left)?;
+        write_values(f, "only right", &self.only_right)?;
+
+        writeln!(f, "biggest discrepancies:")?;
+        for it in &self.discrepancies {
+            writeln!(
+                f,
+                "  #{}: {} vs {}, {} apart",
+                it.rank + 1,
+                it.left,
+                it.right,
+                it.distance
+            )?;
+        }
 
+        Ok(())
+    }
+}
 
-    Ok(result)
+fn simple(columns: &Columns, pair: (usize, usize)) -> AocResult {
+    let (left, right) = columns.pair(pair)?;
+
+    Ok(total_distance(left, right))
 }
 
-async fn advanced(file: FileHandle) -> anyhow::Result<i64> {
-    let (left, right) = parse_lines(file)?;
-    let right_counts = right.iter().fold(HashMap::new(), |mut acc, &num| {
-        *acc.entry(num).or_insert(0) += 1;
-        acc
-    });
+fn advanced(columns: &Columns, pair: (usize, usize)) -> AocResult {
+    let (left, right) = columns.pair(pair)?;
+
+    Ok(similarity_score(left, right))
+}
+
+/// Which two columns are the "left" and "right" list, and how many discrepancies to report.
+pub struct Comparison {
+    pub left: usize,
+    pub right: usize,
+    pub top: usize,
+}
 
-    let mut result = 0;
-    for number in left.iter() {
-        let multiplier = right_counts.get(number).unwrap_or(&0);
-        result += number * multiplier;
+impl Default for Comparison {
+    fn default() -> Self {
+        Self {
+            left: 0,
+            right: 1,
+            top: 10,
+        }
     }
+}
+
+pub struct Day1;
+
+impl Solution for Day1 {
+    const DAY: u8 = 1;
+
+    type Input = Columns;
+    type Config = Comparison;
+
+    fn parse(input: &PuzzleInput) -> AocResult<Self::Input> {
+        Ok(parse_columns(input)?)
+    }
+
+    fn part1(input: &Self::Input, config: &Self::Config) -> AocResult<Answer> {
+        Ok(simple(input, (config.left, config.right))?.into())
+    }
+
+    fn part2(input: &Self::Input, config: &Self::Config) -> AocResult<Answer> {
+        Ok(advanced(input, (config.left, config.right))?.into())
+    }
+
+    fn explain(input: &Self::Input, config: &Self::Config, options: &Explain) -> AocResult<String> {
+        let (left, right) = input.pair((config.left, config.right))?;
+        let report = Report::new(left, right, config.top);
 
-    Ok(result)
+        Ok(match options.format {
+            Format::Text => report.to_string(),
+            Format::Json => serde_json::to_string_pretty(&report)? + "\n",
+            format => anyhow::bail!("Day {} has no {format} report", Self::DAY),
+        })
+    }
 }
 
 // -- tests --
 
-type FileHandle = io::Lines<io::BufReader<File>>;
+#[test]
+fn test_inline_example() {
+    let input = PuzzleInput::from("3   4\n4   3\n2   5\n1   3\n3   9\n3   3\n");
+
+    assert_eq!(Day1::solve(1, &input).expect("Oof 1"), 11);
+    assert_eq!(Day1::solve(2, &input).expect("Oof 2"), 31);
 
-async fn read_lines<P: AsRef<Path>>(filename: P) -> io::Result<FileHandle> {
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
+    let err = Day1::parse(&PuzzleInput::from("3   4\n4   x\n")).expect_err("Not a number");
+    assert!(err.to_string().starts_with("line 2, column 5:"), "{err}");
 }
 
-#[tokio::test]
-async fn test_minimal_simple() {
+#[test]
+fn test_columns() {
+    let input = PuzzleInput::from("3\t4   1\n4 3 1\n  2\t\t5 7\n");
+    let columns = parse_columns(&input).expect("Three columns");
+
+    assert_eq!(columns.count(), 3);
+    assert_eq!(columns.column(0), Some(&[3, 4, 2][..]));
+    assert_eq!(columns.column(2), Some(&[1, 1, 7][..]));
+    assert!(columns.pair((0, 3)).is_err());
+
+    let config = Comparison {
+        left: 2,
+        right: 0,
+        ..Default::default()
+    };
+    assert_eq!(Day1::solve_with(1, &input, &config).expect("Oof 1"), 6);
+    assert_eq!(Day1::solve_with(2, &input, &config).expect("Oof 2"), 0);
+
+    let err = parse_columns(&PuzzleInput::from("1 2\n3 4 5\n")).expect_err("Ragged");
+    assert_eq!((err.line, err.column), (2, 5));
+    let err = parse_columns(&PuzzleInput::from("1 2\n3\n")).expect_err("Ragged");
+    assert_eq!((err.line, err.column), (2, 1));
+}
+
+#[test]
+fn test_streaming_similarity() {
+    let input = PuzzleInput::from("3   4\n4   3\n2   5\n1   3\n3   9\n3   3\n");
+    let (left, right) = count_columns(&input, (0, 1)).expect("Two columns");
+
+    assert_eq!(left.get(3), 3);
+    assert_eq!(right.get(3), 3);
+    assert_eq!(similarity_of_counts(&left, &right), 31);
+    assert!(count_columns(&input, (0, 2)).is_err());
+}
+
+#[test]
+fn test_minimal_simple() {
     let answer = 11;
 
-    let file = read_lines("minimal.txt").await.expect("Should be able to read minimal.txt");
+    let file = aoc_common::input_or_skip!(Day1, "example");
 
-    assert_eq!(simple(file).await.expect("Oof 0"), answer);
+    assert_eq!(Day1::solve(1, &file).expect("Oof 0"), answer);
 }
 
-#[tokio::test]
-async fn test_simple() {
+#[test]
+fn test_simple() {
     let answer = 2375403;
 
-    let file = read_lines("simple.txt").await.expect("Should be able to read simple.txt");
+    let file = aoc_common::input_or_skip!(Day1, "input");
 
-    assert_eq!(simple(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day1::solve(1, &file).expect("Oof 1"), answer);
 }
 
-
-#[tokio::test]
-async fn test_minimal_advanced() {
+#[test]
+fn test_minimal_advanced() {
     let answer = 31;
 
-    let file = read_lines("minimal.txt").await.expect("Should be able to read minimal.txt");
+    let file = aoc_common::input_or_skip!(Day1, "example");
 
-    assert_eq!(advanced(file).await.expect("Oof 0"), answer);
+    assert_eq!(Day1::solve(2, &file).expect("Oof 0"), answer);
 }
 
-#[tokio::test]
-async fn test_advanced() {
+#[test]
+fn test_advanced() {
     let answer = 23082277;
 
-    let file = read_lines("simple.txt").await.expect("Should be able to read advanced.txt");
+    let file = aoc_common::input_or_skip!(Day1, "input");
+
+    assert_eq!(Day1::solve(2, &file).expect("Oof 2"), answer);
+}
+
+#[test]
+fn test_report() {
+    let left = [3, 4, 2, 1, 3, 3];
+    let right = [4, 3, 5, 3, 9, 3];
+    let report = Report::new(&left, &right, 2);
+
+    assert_eq!(report.distance, 11);
+    assert_eq!(report.similarity, 31);
+    // {3, 4} out of {1, 2, 3, 4, 5, 9}:
+    assert!((report.jaccard - 2.0 / 6.0).abs() < 1e-9);
+    // three 3s and one 4:
+    assert_eq!(report.multiset_intersection, 4);
+    assert_eq!(
+        report.contributions[0],
+        Contribution {
+            value: 3,
+            left_count: 3,
+            right_count: 3,
+            score: 27
+        }
+    );
+    assert_eq!(report.only_left, vec![1, 2]);
+    assert_eq!(report.only_right, vec![5, 9]);
+
+    // sorted: 1 2 3 3 3 4 / 3 3 3 4 5 9
+    let distances: Vec<_> = report
+        .discrepancies
+        .iter()
+        .map(|it| (it.rank, it.distance))
+        .collect();
+    assert_eq!(distances, vec![(5, 5), (0, 2)]);
 
-    assert_eq!(advanced(file).await.expect("Oof 2"), answer);
+    let text = Day1::explain(
+        &parse_columns(&PuzzleInput::from("3 4\n1 3\n")).expect("Ok"),
+        &Comparison::default(),
+        &Format::Text.into(),
+    )
+    .expect("Explained");
+    assert!(text.contains("only left (1): 1\n"), "{text}");
+    let json = serde_json::to_value(&report).expect("Serializable");
+    assert_eq!(json["only_right"], serde_json::json!([5, 9]));
 }
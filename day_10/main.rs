@@ -1,32 +1,19 @@
 #![allow(dead_code)]
 
-use std::collections::{BTreeMap, HashSet};
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
+use aoc_common::{Answer, AocResult, Grid, ParseError, Point, PuzzleInput, Solution};
+use std::collections::HashSet;
 
-type Point = (i64, i64);
+type Matrix = Grid<i64>;
+type TopographicMap = (Matrix, Vec<Point>);
 
-type Matrix = BTreeMap<Point, i64>;
+fn build_matrix(input: &PuzzleInput) -> Result<TopographicMap, ParseError> {
+    // impassable tiles ('.') get height -1:
+    let matrix = Grid::parse(input.lines(), |char| {
+        char.to_digit(10).map(|it| it as i64).unwrap_or(-1)
+    })?;
+    let trailheads = matrix.find_all(&0).collect();
 
-#[must_use]
-fn build_matrix(file: FileHandle) -> (Matrix, Vec<Point>) {
-    let mut matrix: Matrix = BTreeMap::new();
-    let mut trailheads = vec![];
-
-    for (x, line) in file.map_while(Result::ok).enumerate() {
-        for (y, char) in line.chars().enumerate() {
-            let number = char.to_digit(10).map(|it| it as i64).unwrap_or(-1);
-            let point = (x as i64, y as i64);
-            matrix.insert(point, number);
-
-            if number == 0 {
-                trailheads.push(point);
-            }
-        }
-    }
-
-    (matrix, trailheads)
+    Ok((matrix, trailheads))
 }
 
 fn walk_trail(position: &Point, height: i64, matrix: &Matrix, found: &mut HashSet<Point>) {
@@ -34,30 +21,19 @@ fn walk_trail(position: &Point, height: i64, matrix: &Matrix, found: &mut HashSe
         found.insert(*position);
         return;
     }
-    let target = &(height + 1);
+    let target = height + 1;
 
     // try 4 directions
-    let left = (position.0, position.1 - 1);
-    if matrix.get(&left).unwrap_or(&-1) == target {
-        walk_trail(&left, *target, matrix, found);
-    };
-    let right = (position.0, position.1 + 1);
-    if matrix.get(&right).unwrap_or(&-1) == target {
-        walk_trail(&right, *target, matrix, found);
-    }
-    let up = (position.0 - 1, position.1);
-    if matrix.get(&up).unwrap_or(&-1) == target {
-        walk_trail(&up, *target, matrix, found);
-    }
-    let down = (position.0 + 1, position.1);
-    if matrix.get(&down).unwrap_or(&-1) == target {
-        walk_trail(&down, *target, matrix, found);
+    for next in matrix.neighbors4(*position) {
+        if matrix[next] == target {
+            walk_trail(&next, target, matrix, found);
+        }
     }
 }
 
 fn score_trailhead(trailhead: &Point, matrix: &Matrix) -> i64 {
     // score determined by amount of 9 points reachable
-    assert_eq!(matrix[trailhead], 0);
+    assert_eq!(matrix[*trailhead], 0);
 
     let mut positions: HashSet<Point> = Default::default();
 
@@ -66,7 +42,7 @@ fn score_trailhead(trailhead: &Point, matrix: &Matrix) -> i64 {
 }
 
 fn point_to_string(point: &Point) -> String {
-    format!("{}.{};", point.0, point.1)
+    format!("{}.{};", point.row, point.col)
 }
 
 fn walk_trail_tracked(
@@ -82,24 +58,13 @@ fn walk_trail_tracked(
         found.insert(route);
         return;
     }
-    let target = &(height + 1);
+    let target = height + 1;
 
     // try 4 directions
-    let left = (position.0, position.1 - 1);
-    if matrix.get(&left).unwrap_or(&-1) == target {
-        walk_trail_tracked(&left, *target, matrix, route.clone(), found);
-    };
-    let right = (position.0, position.1 + 1);
-    if matrix.get(&right).unwrap_or(&-1) == target {
-        walk_trail_tracked(&right, *target, matrix, route.clone(), found);
-    }
-    let up = (position.0 - 1, position.1);
-    if matrix.get(&up).unwrap_or(&-1) == target {
-        walk_trail_tracked(&up, *target, matrix, route.clone(), found);
-    }
-    let down = (position.0 + 1, position.1);
-    if matrix.get(&down).unwrap_or(&-1) == target {
-        walk_trail_tracked(&down, *target, matrix, route.clone(), found);
+    for next in matrix.neighbors4(*position) {
+        if matrix[next] == target {
+            walk_trail_tracked(&next, target, matrix, route.clone(), found);
+        }
     }
 }
 
@@ -111,76 +76,76 @@ fn rate_trailhead(trailhead: &Point, matrix: &Matrix) -> i64 {
     positions.len() as i64
 }
 
-async fn simple(file: FileHandle) -> anyhow::Result<i64> {
-    let (matrix, trailheads) = build_matrix(file);
-
+fn simple((matrix, trailheads): &TopographicMap) -> AocResult {
     let result = trailheads
         .iter()
-        .map(|th| score_trailhead(th, &matrix))
+        .map(|th| score_trailhead(th, matrix))
         .sum();
 
     Ok(result)
 }
 
-async fn advanced(file: FileHandle) -> anyhow::Result<i64> {
-    let (matrix, trailheads) = build_matrix(file);
-    let result = trailheads
-        .iter()
-        .map(|th| rate_trailhead(th, &matrix))
-        .sum();
+fn advanced((matrix, trailheads): &TopographicMap) -> AocResult {
+    let result = trailheads.iter().map(|th| rate_trailhead(th, matrix)).sum();
 
     Ok(result)
 }
 
-// -- tests --
+pub struct Day10;
+
+impl Solution for Day10 {
+    const DAY: u8 = 10;
+
+    type Input = TopographicMap;
+    type Config = ();
 
-type FileHandle = io::Lines<io::BufReader<File>>;
+    fn parse(input: &PuzzleInput) -> AocResult<Self::Input> {
+        Ok(build_matrix(input)?)
+    }
+
+    fn part1(input: &Self::Input, _config: &()) -> AocResult<Answer> {
+        Ok(simple(input)?.into())
+    }
 
-async fn read_lines<P: AsRef<Path>>(filename: P) -> io::Result<FileHandle> {
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
+    fn part2(input: &Self::Input, _config: &()) -> AocResult<Answer> {
+        Ok(advanced(input)?.into())
+    }
 }
 
-#[tokio::test]
-async fn test_simple_minimal() {
+// -- tests --
+
+#[test]
+fn test_simple_minimal() {
     let answer = 36;
 
-    let file = read_lines("minimal.txt")
-        .await
-        .expect("Should be able to read minimal.txt");
+    let file = aoc_common::input_or_skip!(Day10, "example");
 
-    assert_eq!(simple(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day10::solve(1, &file).expect("Oof 1"), answer);
 }
 
-#[tokio::test]
-async fn test_simple() {
+#[test]
+fn test_simple() {
     let answer = 607;
 
-    let file = read_lines("input.txt")
-        .await
-        .expect("Should be able to read input.txt");
+    let file = aoc_common::input_or_skip!(Day10, "input");
 
-    assert_eq!(simple(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day10::solve(1, &file).expect("Oof 1"), answer);
 }
 
-#[tokio::test]
-async fn test_advanced_minimal() {
+#[test]
+fn test_advanced_minimal() {
     let answer = 81;
 
-    let file = read_lines("minimal.txt")
-        .await
-        .expect("Should be able to read minimal.txt");
+    let file = aoc_common::input_or_skip!(Day10, "example");
 
-    assert_eq!(advanced(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day10::solve(2, &file).expect("Oof 1"), answer);
 }
 
-#[tokio::test]
-async fn test_advanced() {
+#[test]
+fn test_advanced() {
     let answer = 1384;
 
-    let file = read_lines("input.txt")
-        .await
-        .expect("Should be able to read input.txt");
+    let file = aoc_common::input_or_skip!(Day10, "input");
 
-    assert_eq!(advanced(file).await.expect("Oof 2"), answer);
+    assert_eq!(Day10::solve(2, &file).expect("Oof 2"), answer);
 }
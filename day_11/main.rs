@@ -1,14 +1,10 @@
 #![allow(dead_code)]
+use aoc_common::{parse_in, Answer, AocResult, ParseError, PuzzleInput, Solution};
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
-use std::sync::{Arc, RwLock};
-use std::thread::{self, JoinHandle};
 
 fn even_digits(num: &i64) -> bool {
     let string = num.to_string();
-    string.len() % 2 == 0
+    string.len().is_multiple_of(2)
 }
 
 fn split_in_half(num_even_digits: &i64) -> (i64, i64) {
@@ -42,151 +38,164 @@ fn blink(stones: &mut Vec<i64>, times: i64) {
     blink(stones, times - 1)
 }
 
-type SharedCache = Arc<RwLock<HashMap<(i64, i64), i64>>>;
+/// {(value, steps): result}, shared between all starting stones since they run into the same numbers.
+type Cache = HashMap<(i64, i64), i64>;
 
-fn blink_v2(stone: i64, times: i64, shared_cache: &SharedCache) -> i64 {
+fn blink_v2(stone: i64, times: i64, cache: &mut Cache) -> i64 {
     let cache_key = (stone, times);
 
     if times == 0 {
         return 1;
     }
 
-    if let Ok(cache_lock) = shared_cache.read() {
-        if cache_lock.contains_key(&cache_key) {
-            return cache_lock[&cache_key];
-        }
+    if let Some(&result) = cache.get(&cache_key) {
+        return result;
     }
 
     let result = if stone == 0 {
-        blink_v2(1, times - 1, shared_cache)
+        blink_v2(1, times - 1, cache)
     } else if even_digits(&stone) {
         // note: order is ignored for performance here; could be required later?
         let (first, second) = split_in_half(&stone);
 
-        blink_v2(first, times - 1, shared_cache) + blink_v2(second, times - 1, shared_cache)
+        blink_v2(first, times - 1, cache) + blink_v2(second, times - 1, cache)
     } else {
-        blink_v2(stone * 2024, times - 1, shared_cache)
+        blink_v2(stone * 2024, times - 1, cache)
     };
 
-    if let Ok(mut cache_lock) = shared_cache.write() {
-        cache_lock.insert(cache_key, result);
-    }
+    cache.insert(cache_key, result);
 
     result
 }
 
-async fn simple(file: FileHandle, n: i64) -> anyhow::Result<i64> {
-    if let Some(line) = file.map_while(Result::ok).next() {
-        let mut stones: Vec<i64> = line
+fn parse_stones(input: &PuzzleInput) -> Result<Vec<i64>, ParseError> {
+    match input.lines().next() {
+        Some(line) => line
             .split(' ')
-            .map(|it| it.parse().expect("Should be numbers"))
-            .collect();
-
-        blink(&mut stones, n);
-        return Ok(stones.len() as i64);
+            .map(|it| parse_in(line, it, "a number").map_err(|err| err.on_line(1)))
+            .collect(),
+        None => Ok(vec![]),
     }
-    Ok(0)
 }
 
-fn join_all<T: Sized>(futures: Vec<JoinHandle<T>>) -> Vec<T> {
-    futures
-        .into_iter()
-        .map(|it| it.join().expect("Should be fine"))
-        .collect()
+fn simple(stones: &[i64], n: i64) -> AocResult {
+    let mut stones = stones.to_vec();
+
+    blink(&mut stones, n);
+    Ok(stones.len() as i64)
 }
 
-async fn advanced(file: FileHandle, n: i64) -> anyhow::Result<i64> {
-    if let Some(line) = file.map_while(Result::ok).next() {
-        let stones: Vec<i64> = line
-            .split(' ')
-            .map(|it| it.parse().expect("Should be numbers"))
-            .collect();
+fn advanced(stones: &[i64], n: i64) -> AocResult {
+    let mut cache = Cache::default();
 
-        let mut futures = vec![];
+    Ok(stones
+        .iter()
+        .map(|&stone| blink_v2(stone, n, &mut cache))
+        .sum())
+}
 
-        // {(value, steps): result}
-        let shared_cache: SharedCache = Default::default();
+/// How many times the stones blink in each part.
+pub struct Blinks {
+    pub part1: i64,
+    pub part2: i64,
+}
 
-        for stone in stones {
-            // one thread per initial stone
-            let cache_clone = shared_cache.clone();
-            futures.push(thread::spawn(move || blink_v2(stone, n, &cache_clone)));
+impl Default for Blinks {
+    fn default() -> Self {
+        Self {
+            part1: 25,
+            part2: 75,
         }
-
-        return Ok(join_all(futures).iter().sum());
     }
-    Ok(0)
 }
 
-// -- tests --
+pub struct Day11;
 
-type FileHandle = io::Lines<io::BufReader<File>>;
+impl Solution for Day11 {
+    const DAY: u8 = 11;
 
-async fn read_lines<P: AsRef<Path>>(filename: P) -> io::Result<FileHandle> {
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
+    type Input = Vec<i64>;
+    type Config = Blinks;
+
+    fn parse(input: &PuzzleInput) -> AocResult<Self::Input> {
+        Ok(parse_stones(input)?)
+    }
+
+    fn part1(input: &Self::Input, config: &Self::Config) -> AocResult<Answer> {
+        Ok(simple(input, config.part1)?.into())
+    }
+
+    fn part2(input: &Self::Input, config: &Self::Config) -> AocResult<Answer> {
+        Ok(advanced(input, config.part2)?.into())
+    }
 }
 
-#[tokio::test]
-async fn test_simple_minimal_6() {
-    let file = read_lines("minimal.txt")
-        .await
-        .expect("Should be able to read minimal.txt");
+// -- tests --
+
+#[test]
+fn test_simple_minimal_6() {
+    let blinks = Blinks {
+        part1: 6,
+        ..Default::default()
+    };
+    let file = aoc_common::input_or_skip!(Day11, "example");
 
-    assert_eq!(simple(file, 6).await.expect("Oof 1"), 22);
+    assert_eq!(Day11::solve_with(1, &file, &blinks).expect("Oof 1"), 22);
 }
 
-#[tokio::test]
-async fn test_simple_minimal_25() {
-    let file = read_lines("minimal.txt")
-        .await
-        .expect("Should be able to read minimal.txt");
+#[test]
+fn test_simple_minimal_25() {
+    let file = aoc_common::input_or_skip!(Day11, "example");
 
-    assert_eq!(simple(file, 25).await.expect("Oof 1"), 55312);
+    assert_eq!(Day11::solve(1, &file).expect("Oof 1"), 55312);
 }
 
-#[tokio::test]
-async fn test_simple() {
+#[test]
+fn test_simple() {
     let answer = 203457;
 
-    let file = read_lines("input.txt")
-        .await
-        .expect("Should be able to read input.txt");
+    let file = aoc_common::input_or_skip!(Day11, "input");
 
-    assert_eq!(simple(file, 25).await.expect("Oof 1"), answer);
+    assert_eq!(Day11::solve(1, &file).expect("Oof 1"), answer);
 }
 
-#[tokio::test]
-async fn test_simple_minimal_6_v2() {
-    let file = read_lines("minimal.txt")
-        .await
-        .expect("Should be able to read minimal.txt");
+#[test]
+fn test_simple_minimal_6_v2() {
+    let blinks = Blinks {
+        part2: 6,
+        ..Default::default()
+    };
+    let file = aoc_common::input_or_skip!(Day11, "example");
 
-    assert_eq!(advanced(file, 6).await.expect("Oof 1"), 22);
+    assert_eq!(Day11::solve_with(2, &file, &blinks).expect("Oof 1"), 22);
 }
 
-#[tokio::test]
-async fn test_simple_minimal_25_v2() {
-    let file = read_lines("minimal.txt")
-        .await
-        .expect("Should be able to read minimal.txt");
+#[test]
+fn test_simple_minimal_25_v2() {
+    let blinks = Blinks {
+        part2: 25,
+        ..Default::default()
+    };
+    let file = aoc_common::input_or_skip!(Day11, "example");
 
-    assert_eq!(advanced(file, 25).await.expect("Oof 1"), 55312);
+    assert_eq!(Day11::solve_with(2, &file, &blinks).expect("Oof 1"), 55312);
 }
 
-#[tokio::test]
-async fn test_simple_v2() {
+#[test]
+fn test_simple_v2() {
     let answer = 203457;
 
-    let file = read_lines("input.txt")
-        .await
-        .expect("Should be able to read input.txt");
+    let blinks = Blinks {
+        part2: 25,
+        ..Default::default()
+    };
+    let file = aoc_common::input_or_skip!(Day11, "input");
 
-    assert_eq!(advanced(file, 25).await.expect("Oof 1"), answer);
+    assert_eq!(Day11::solve_with(2, &file, &blinks).expect("Oof 1"), answer);
 }
 
-// #[tokio::test]
-// async fn test_simulate_blink_v1() {
+// #[test]
+// fn test_simulate_blink_v1() {
 //     /**
 //     50 -> 1
 //     49 -> 1
@@ -244,19 +253,17 @@ async fn test_simple_v2() {
 //     assert_eq!(v.len(), 437102505);
 // }
 
-#[tokio::test]
-async fn test_simulate_blink_v2() {
-    let shared_cache: SharedCache = Default::default();
-    assert_eq!(blink_v2(0, 49, &shared_cache), 437102505); // 34s before, 0.01s after
+#[test]
+fn test_simulate_blink_v2() {
+    let mut cache = Cache::default();
+    assert_eq!(blink_v2(0, 49, &mut cache), 437102505); // 34s before, 0.01s after
 }
 
-#[tokio::test]
-async fn test_advanced() {
+#[test]
+fn test_advanced() {
     let answer = 241394363462435;
 
-    let file = read_lines("input.txt")
-        .await
-        .expect("Should be able to read input.txt");
+    let file = aoc_common::input_or_skip!(Day11, "input");
 
-    assert_eq!(advanced(file, 75).await.expect("Oof 2"), answer);
+    assert_eq!(Day11::solve(2, &file).expect("Oof 2"), answer);
 }
@@ -1,86 +1,38 @@
 #![allow(dead_code)]
 
-use std::collections::{BTreeMap, HashMap};
-use std::fmt::Display;
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
-
-const DIRECTIONS: [Direction; 4] = [
-    Direction::Up,
-    Direction::Right,
-    Direction::Down,
-    Direction::Left,
-];
-
-type Point = (i64, i64);
-type Matrix = BTreeMap<Point, char>;
-type PlotMap = BTreeMap<Point, i64>;
-
-fn draw<D: Display>(plot_map: &BTreeMap<Point, D>) {
-    let mut cur_x = 0;
-
-    for (point, idx) in plot_map {
-        if point.0 != cur_x {
-            println!();
-            cur_x = point.0;
-        }
-        print!("{idx}");
-    }
-    println!();
-}
+use aoc_common::{Answer, AocResult, Direction, Grid, ParseError, Point, PuzzleInput, Solution};
+use std::collections::HashMap;
 
-fn parse_matrix_file(file: FileHandle) -> Matrix {
-    let mut matrix: Matrix = Default::default();
-    for (x, line) in file.map_while(Result::ok).enumerate() {
-        for (y, plant) in line.chars().enumerate() {
-            matrix.insert((x as i64, y as i64), plant);
-        }
-    }
+type Matrix = Grid<char>;
+type PlotMap = Grid<i64>;
 
-    matrix
+fn draw(plot_map: &PlotMap) {
+    println!("{plot_map}");
 }
 
-fn parse_matrix_str(rows: &str) -> Matrix {
-    let mut matrix: Matrix = Default::default();
-    for (x, line) in rows.lines().enumerate() {
-        for (y, plant) in line.chars().enumerate() {
-            matrix.insert((x as i64, y as i64), plant);
-        }
-    }
-
-    matrix
+fn parse_matrix_file(input: &PuzzleInput) -> Result<Matrix, ParseError> {
+    Grid::chars(input.lines())
 }
 
-enum Direction {
-    Up,
-    Right,
-    Down,
-    Left,
-}
-
-impl Direction {
-    pub fn mutate(&self, other: &Point) -> Point {
-        match self {
-            Direction::Up => (other.0 - 1, other.1),
-            Direction::Right => (other.0, other.1 + 1),
-            Direction::Down => (other.0 + 1, other.1),
-            Direction::Left => (other.0, other.1 - 1),
-        }
-    }
+fn parse_matrix_str(rows: &str) -> Result<Matrix, ParseError> {
+    Grid::chars(rows.lines())
 }
 
-fn walk(point: &Point, plant: &char, plot_idx: &i64, plot_map: &mut PlotMap, matrix: &Matrix) {
-    if plot_map.contains_key(point) {
+fn walk(
+    point: &Point,
+    plant: &char,
+    plot_idx: &i64,
+    plot_map: &mut Grid<Option<i64>>,
+    matrix: &Matrix,
+) {
+    if plot_map[*point].is_some() {
         // already seen
         return;
     }
 
-    plot_map.insert(*point, *plot_idx);
-    for direction in DIRECTIONS {
-        let new_point = direction.mutate(point);
-        let new_plant = matrix.get(&new_point).unwrap_or(&' ');
-        if new_plant == plant {
+    plot_map[*point] = Some(*plot_idx);
+    for new_point in matrix.neighbors4(*point) {
+        if &matrix[new_point] == plant {
             walk(&new_point, plant, plot_idx, plot_map, matrix);
         }
     }
@@ -90,30 +42,28 @@ fn assign_ids(matrix: &Matrix) -> PlotMap {
     // for every point in matrix without plot id:
     // continue walking everything with same plant.
     let mut plot_idx: i64 = 0;
-    let mut plot_indices: PlotMap = Default::default();
-    for (point, plant) in matrix {
-        if !plot_indices.contains_key(point) {
-            walk(point, plant, &plot_idx, &mut plot_indices, &matrix);
+    let mut plot_indices = matrix.map(|_| None);
+    for (point, plant) in matrix.iter() {
+        if plot_indices[point].is_none() {
+            walk(&point, plant, &plot_idx, &mut plot_indices, matrix);
             plot_idx += 1;
         }
     }
 
-    plot_indices
+    plot_indices.map(|idx| idx.expect("Every plot should be walked"))
 }
 
-fn plotmap_from_file(file: FileHandle) -> PlotMap {
-    let matrix = parse_matrix_file(file);
-    assign_ids(&matrix)
+fn plotmap_from_file(input: &PuzzleInput) -> Result<PlotMap, ParseError> {
+    let matrix = parse_matrix_file(input)?;
+    Ok(assign_ids(&matrix))
 }
 
-fn plotmap_from_string(input: &str) -> PlotMap {
-    let matrix = parse_matrix_str(input);
-    assign_ids(&matrix)
+fn plotmap_from_string(input: &str) -> Result<PlotMap, ParseError> {
+    let matrix = parse_matrix_str(input)?;
+    Ok(assign_ids(&matrix))
 }
 
-async fn simple(file: FileHandle) -> anyhow::Result<i64> {
-    let plots = plotmap_from_file(file);
-
+fn simple(plots: &PlotMap) -> AocResult {
     // draw(&plots);
 
     let mut area_per_plot_idx: HashMap<i64, i64> = Default::default();
@@ -121,11 +71,11 @@ async fn simple(file: FileHandle) -> anyhow::Result<i64> {
 
     // now loop through matrix again and calculate score:
 
-    for (point, my_plot) in &plots {
+    for (point, my_plot) in plots.iter() {
         let mut perimeter = 4;
-        for direction in DIRECTIONS {
-            let other_point = direction.mutate(point);
-            let other_plot = plots.get(&other_point).unwrap_or(&-1);
+        for direction in Direction::CARDINAL {
+            let other_point = point + direction;
+            let other_plot = plots.get(other_point).unwrap_or(&-1);
 
             if my_plot == other_plot {
                 perimeter -= 1;
@@ -147,9 +97,9 @@ async fn simple(file: FileHandle) -> anyhow::Result<i64> {
 
 fn count_neighbors(point: &Point, plot_idx: &i64, plots: &PlotMap) -> i64 {
     let mut neighbors = 0;
-    for direction in DIRECTIONS {
-        let other_point = direction.mutate(point);
-        let other_plot = plots.get(&other_point).unwrap_or(&-1);
+    for direction in Direction::CARDINAL {
+        let other_point = *point + direction;
+        let other_plot = plots.get(other_point).unwrap_or(&-1);
         if other_plot == plot_idx {
             neighbors += 1;
         }
@@ -162,6 +112,7 @@ fn count_neighbors(point: &Point, plot_idx: &i64, plots: &PlotMap) -> i64 {
 /// ? is current block
 /// X is related type
 /// Don't forget other orientations (e.g. vertical if example is horizontal)!!
+#[allow(clippy::enum_variant_names)] // TShape is the shape, not a kind of Shape
 enum Shape {
     /// 0 neighbors
     /// OOO
@@ -201,20 +152,20 @@ enum Shape {
 }
 
 fn count_corners(point: &Point, plot_idx: &i64, plots: &PlotMap) -> i64 {
-    let point_left = Direction::Left.mutate(point);
-    let value_left = plots.get(&point_left).unwrap_or(&-1);
+    let point_left = *point + Direction::Left;
+    let value_left = plots.get(point_left).unwrap_or(&-1);
     let left_is_neighbor = value_left == plot_idx;
 
-    let point_right = Direction::Right.mutate(point);
-    let value_right = plots.get(&point_right).unwrap_or(&-1);
+    let point_right = *point + Direction::Right;
+    let value_right = plots.get(point_right).unwrap_or(&-1);
     let right_is_neighbor = value_right == plot_idx;
 
-    let point_top = Direction::Up.mutate(point);
-    let value_top = plots.get(&point_top).unwrap_or(&-1);
+    let point_top = *point + Direction::Up;
+    let value_top = plots.get(point_top).unwrap_or(&-1);
     let top_is_neighbor = value_top == plot_idx;
 
-    let point_down = Direction::Down.mutate(point);
-    let value_down = plots.get(&point_down).unwrap_or(&-1);
+    let point_down = *point + Direction::Down;
+    let value_down = plots.get(point_down).unwrap_or(&-1);
     let down_is_neighbor = value_down == plot_idx;
 
     let neighbors = [
@@ -256,10 +207,10 @@ fn count_corners(point: &Point, plot_idx: &i64, plots: &PlotMap) -> i64 {
                 todo!()
             } else if !down_is_neighbor {
                 // check up left and right
-                let diagonal_1 = (point.0 - 1, point.1 - 1);
-                let value_diagonal_1 = plots.get(&diagonal_1).unwrap_or(&-1) == plot_idx;
-                let diagonal_2 = (point.0 - 1, point.1 + 1);
-                let value_diagonal_2 = plots.get(&diagonal_2).unwrap_or(&-1) == plot_idx;
+                let diagonal_1 = *point + Direction::UpLeft;
+                let value_diagonal_1 = plots.get(diagonal_1).unwrap_or(&-1) == plot_idx;
+                let diagonal_2 = *point + Direction::UpRight;
+                let value_diagonal_2 = plots.get(diagonal_2).unwrap_or(&-1) == plot_idx;
 
                 (value_diagonal_1 as i64) + (value_diagonal_2 as i64)
             } else if !left_is_neighbor {
@@ -283,12 +234,12 @@ fn count_corners(point: &Point, plot_idx: &i64, plots: &PlotMap) -> i64 {
 fn count_sides(target_plot: i64, plots: &PlotMap) -> i64 {
     // debug function
     let mut result = 0;
-    for (point, plot_idx) in plots {
+    for (point, plot_idx) in plots.iter() {
         if &target_plot != plot_idx {
             continue;
         }
 
-        let n_corners = count_corners(point, plot_idx, plots);
+        let n_corners = count_corners(&point, plot_idx, plots);
 
         result += n_corners;
     }
@@ -296,17 +247,16 @@ fn count_sides(target_plot: i64, plots: &PlotMap) -> i64 {
     result
 }
 
-async fn advanced(file: FileHandle) -> anyhow::Result<i64> {
+fn advanced(plots: &PlotMap) -> AocResult {
     // uses 'number of sides' instead of 'perimeter'
-    let plots = plotmap_from_file(file);
-    draw(&plots);
+    draw(plots);
 
     let mut area_per_plot_idx: HashMap<i64, i64> = Default::default();
     let mut corners_per_plot_idx: HashMap<i64, i64> = Default::default();
 
-    for (point, plot_idx) in &plots {
+    for (point, plot_idx) in plots.iter() {
         *area_per_plot_idx.entry(*plot_idx).or_default() += 1;
-        let n_corners = count_corners(point, plot_idx, &plots);
+        let n_corners = count_corners(&point, plot_idx, plots);
 
         *corners_per_plot_idx.entry(*plot_idx).or_default() += n_corners;
     }
@@ -322,171 +272,166 @@ async fn advanced(file: FileHandle) -> anyhow::Result<i64> {
         .sum())
 }
 
-// -- tests --
+pub struct Day12;
 
-type FileHandle = io::Lines<io::BufReader<File>>;
+impl Solution for Day12 {
+    const DAY: u8 = 12;
+    const UNSOLVED: &'static [u8] = &[2];
+
+    type Input = PlotMap;
+    type Config = ();
+
+    fn parse(input: &PuzzleInput) -> AocResult<Self::Input> {
+        Ok(plotmap_from_file(input)?)
+    }
 
-async fn read_lines<P: AsRef<Path>>(filename: P) -> io::Result<FileHandle> {
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
+    fn part1(input: &Self::Input, _config: &()) -> AocResult<Answer> {
+        Ok(simple(input)?.into())
+    }
+
+    fn part2(_input: &Self::Input, _config: &()) -> AocResult<Answer> {
+        // `advanced` still hits the todo!()s in `count_corners`:
+        Ok(Answer::Unsolved)
+    }
 }
 
-// #[tokio::test]
-// async fn test_simple_minimal_0() {
+// -- tests --
+
+// #[test]
+// fn test_simple_minimal_0() {
 //     let answer = 0;
 //
-//     let file = read_lines("minimal0.txt")
-//         .await
-//         .expect("Should be able to read minimal0.txt");
+//     let file = aoc_common::input_or_skip!(Day12, "example0");
 //
-//     assert_eq!(simple(file).await.expect("Oof 1"), answer);
+//     assert_eq!(Day12::solve(1, &file).expect("Oof 1"), answer);
 // }
 
-#[tokio::test]
-async fn test_simple_minimal_1() {
+#[test]
+fn test_simple_minimal_1() {
     let answer = 140;
 
-    let file = read_lines("minimal1.txt")
-        .await
-        .expect("Should be able to read minimal1.txt");
+    let file = aoc_common::input_or_skip!(Day12, "example1");
 
-    assert_eq!(simple(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day12::solve(1, &file).expect("Oof 1"), answer);
 }
 
-#[tokio::test]
-async fn test_simple_minimal_2() {
+#[test]
+fn test_simple_minimal_2() {
     let answer = 772;
 
-    let file = read_lines("minimal2.txt")
-        .await
-        .expect("Should be able to read minimal2.txt");
+    let file = aoc_common::input_or_skip!(Day12, "example2");
 
-    assert_eq!(simple(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day12::solve(1, &file).expect("Oof 1"), answer);
 }
 
-#[tokio::test]
-async fn test_simple_minimal_3() {
+#[test]
+fn test_simple_minimal_3() {
     let answer = 1930;
 
-    let file = read_lines("minimal3.txt")
-        .await
-        .expect("Should be able to read minimal3.txt");
+    let file = aoc_common::input_or_skip!(Day12, "example3");
 
-    assert_eq!(simple(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day12::solve(1, &file).expect("Oof 1"), answer);
 }
 
-#[tokio::test]
-async fn test_simple() {
+#[test]
+fn test_simple() {
     let answer = 1424006;
 
-    let file = read_lines("input.txt")
-        .await
-        .expect("Should be able to read input.txt");
+    let file = aoc_common::input_or_skip!(Day12, "input");
 
-    assert_eq!(simple(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day12::solve(1, &file).expect("Oof 1"), answer);
 }
 
-#[tokio::test]
-async fn test_counting_corners() {
+#[test]
+fn test_counting_corners() {
     let grid = plotmap_from_string(
         r"YXXX
 YZZZ
 YVVV
 VVVV",
-    );
+    )
+    .expect("Should be rectangular");
     // Y = 0
     // X = 1
     // Z = 2
     // V = 3
     // draw(&grid);
 
-    /// Horizontal (Z/2)
+    // Horizontal (Z/2)
 
     // top left should have 2 corners
-    assert_eq!(count_corners(&(1, 1), &2, &grid), 2);
+    assert_eq!(count_corners(&Point::new(1, 1), &2, &grid), 2);
     // center should have 0 corners
-    assert_eq!(count_corners(&(1, 2), &2, &grid), 0);
+    assert_eq!(count_corners(&Point::new(1, 2), &2, &grid), 0);
     // top right should have 2 corners
-    assert_eq!(count_corners(&(1, 3), &2, &grid), 2);
+    assert_eq!(count_corners(&Point::new(1, 3), &2, &grid), 2);
     // line should have 4 sides
     assert_eq!(count_sides(2, &grid), 4);
 
-    /// Vertical (Y/0)
-    assert_eq!(count_corners(&(0, 0), &0, &grid), 2);
-    assert_eq!(count_corners(&(1, 0), &0, &grid), 0);
-    assert_eq!(count_corners(&(2, 0), &0, &grid), 2);
+    // Vertical (Y/0)
+    assert_eq!(count_corners(&Point::new(0, 0), &0, &grid), 2);
+    assert_eq!(count_corners(&Point::new(1, 0), &0, &grid), 0);
+    assert_eq!(count_corners(&Point::new(2, 0), &0, &grid), 2);
     // line should have 4 sides
     assert_eq!(count_sides(0, &grid), 4);
 
-    /// Weird (V/3)
+    // Weird (V/3)
     // bottom left:
-    assert_eq!(count_corners(&(3, 0), &3, &grid), 2);
-    assert_eq!(count_corners(&(3, 1), &3, &grid), 1);
+    assert_eq!(count_corners(&Point::new(3, 0), &3, &grid), 2);
+    assert_eq!(count_corners(&Point::new(3, 1), &3, &grid), 1);
     assert_eq!(count_sides(3, &grid), 6)
 }
 
-// #[tokio::test]
-// async fn test_advanced_minimal_1() {
+// #[test]
+// fn test_advanced_minimal_1() {
 //     let answer = 80;
 //
-//     let file = read_lines("minimal1.txt")
-//         .await
-//         .expect("Should be able to read minimal1.txt");
+//     let file = aoc_common::input_or_skip!(Day12, "example1");
 //
-//     assert_eq!(advanced(file).await.expect("Oof 1"), answer);
+//     assert_eq!(Day12::solve(2, &file).expect("Oof 1"), answer);
 // }
 
-// #[tokio::test]
-// async fn test_advanced_minimal_2() {
+// #[test]
+// fn test_advanced_minimal_2() {
 //     let answer = 436;
 //
-//     let file = read_lines("minimal2.txt")
-//         .await
-//         .expect("Should be able to read minimal2.txt");
+//     let file = aoc_common::input_or_skip!(Day12, "example2");
 //
-//     assert_eq!(advanced(file).await.expect("Oof 1"), answer);
+//     assert_eq!(Day12::solve(2, &file).expect("Oof 1"), answer);
 // }
 //
-// #[tokio::test]
-// async fn test_advanced_minimal_3() {
+// #[test]
+// fn test_advanced_minimal_3() {
 //     let answer = 1206;
 //
-//     let file = read_lines("minimal3.txt")
-//         .await
-//         .expect("Should be able to read minimal3.txt");
+//     let file = aoc_common::input_or_skip!(Day12, "example3");
 //
-//     assert_eq!(advanced(file).await.expect("Oof 1"), answer);
+//     assert_eq!(Day12::solve(2, &file).expect("Oof 1"), answer);
 // }
 //
-// #[tokio::test]
-// async fn test_advanced_minimal_4() {
+// #[test]
+// fn test_advanced_minimal_4() {
 //     let answer = 236;
 //
-//     let file = read_lines("minimal4.txt")
-//         .await
-//         .expect("Should be able to read minimal4.txt");
+//     let file = aoc_common::input_or_skip!(Day12, "example4");
 //
-//     assert_eq!(advanced(file).await.expect("Oof 1"), answer);
+//     assert_eq!(Day12::solve(2, &file).expect("Oof 1"), answer);
 // }
 //
-// #[tokio::test]
-// async fn test_advanced_minimal_5() {
+// #[test]
+// fn test_advanced_minimal_5() {
 //     let answer = 368;
 //
-//     let file = read_lines("minimal5.txt")
-//         .await
-//         .expect("Should be able to read minimal5.txt");
+//     let file = aoc_common::input_or_skip!(Day12, "example5");
 //
-//     assert_eq!(advanced(file).await.expect("Oof 1"), answer);
+//     assert_eq!(Day12::solve(2, &file).expect("Oof 1"), answer);
 // }
 
-// #[tokio::test]
-// async fn test_advanced() {
+// #[test]
+// fn test_advanced() {
 //     let answer = 0;
 //
-//     let file = read_lines("input.txt")
-//         .await
-//         .expect("Should be able to read input.txt");
+//     let file = aoc_common::input_or_skip!(Day12, "input");
 //
-//     assert_eq!(advanced(file).await.expect("Oof 2"), answer);
+//     assert_eq!(Day12::solve(2, &file).expect("Oof 2"), answer);
 // }
@@ -1,25 +1,25 @@
 #![allow(dead_code)]
 
+use aoc_common::{parse_in, Answer, AocResult, ParseError, PuzzleInput, Solution};
 use image::{ImageBuffer, Luma};
 use once_cell::sync::Lazy;
 use regex::Regex;
 use std::cmp::Ordering;
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::{self, BufRead};
+use std::fs;
 use std::path::Path;
 
 type Point = (i64, i64);
 
-fn parse_point(value: &str) -> Option<Point> {
-    let mut parts = value.split(",");
-    let left = parts.next()?.trim();
-    let right = parts.next()?.trim();
+fn parse_point(line: &str, value: &str) -> Result<Point, ParseError> {
+    let (left, right) = value
+        .split_once(",")
+        .ok_or_else(|| ParseError::in_line(line, value, "a point like `3,-3`"))?;
 
-    let left_i: i64 = left.parse().ok()?;
-    let right_i: i64 = right.parse().ok()?;
+    let left_i: i64 = parse_in(line, left.trim(), "a number")?;
+    let right_i: i64 = parse_in(line, right.trim(), "a number")?;
 
-    Some((left_i, right_i))
+    Ok((left_i, right_i))
 }
 
 fn wrap(number: i64, max: i64) -> i64 {
@@ -32,26 +32,28 @@ trait Moves {
 }
 
 #[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug)]
-struct Robot {
+pub struct Robot {
     pub position: Point,
     pub velocity: Point,
 }
 
 impl Robot {
-    fn parse(line: &str) -> Self {
+    fn parse(line: &str) -> Result<Self, ParseError> {
         static RE: Lazy<Regex> =
             Lazy::new(|| Regex::new(r"p=(-?\d+,-?\d+) v=(-?\d+,-?\d+)").unwrap());
 
-        let caps = RE.captures(line).expect("Invalid Robot");
-        let mut caps_i = caps.iter();
-        caps_i.next();
-        let p = caps_i.next().flatten().expect("Should exist").as_str();
-        let v = caps_i.next().flatten().expect("Should exist").as_str();
-
-        Self {
-            position: parse_point(p).expect("Should be valid position"),
-            velocity: parse_point(v).expect("Should be valid velocity"),
-        }
+        let Some((_, [p, v])) = RE.captures(line).map(|caps| caps.extract()) else {
+            return Err(ParseError::in_line(
+                line,
+                line,
+                "a robot like `p=0,4 v=3,-3`",
+            ));
+        };
+
+        Ok(Self {
+            position: parse_point(line, p)?,
+            velocity: parse_point(line, v)?,
+        })
     }
 }
 
@@ -145,23 +147,23 @@ fn quadrant_score(robots: &[Robot], width: i64, height: i64) -> i64 {
         .expect("What could go wrong")
 }
 
-fn parse_robots(file: FileHandle) -> Vec<Robot> {
+fn parse_robots(input: &PuzzleInput) -> Result<Vec<Robot>, ParseError> {
     let mut robots: Vec<Robot> = Default::default();
 
-    for line in file.map_while(Result::ok) {
+    for (idx, line) in input.lines().enumerate() {
         if line.starts_with("#") {
             continue;
         }
 
-        let robot = Robot::parse(&line);
+        let robot = Robot::parse(line).map_err(|err| err.on_line(idx + 1))?;
         robots.push(robot);
     }
 
-    robots
+    Ok(robots)
 }
 
-async fn simple(file: FileHandle, width: i64, height: i64, times: i64) -> anyhow::Result<i64> {
-    let mut robots = parse_robots(file);
+fn simple(robots: &[Robot], width: i64, height: i64, times: i64) -> AocResult {
+    let mut robots = robots.to_vec();
 
     // draw(&robots, width, height);
     robots.moves(times, width, height);
@@ -171,7 +173,10 @@ async fn simple(file: FileHandle, width: i64, height: i64, times: i64) -> anyhow
 }
 
 fn to_image(robots: &[Robot], width: i64, height: i64, iteration: i64) {
-    let filename = format!("./output/img_{}.png", iteration);
+    let output = Path::new(env!("CARGO_MANIFEST_DIR")).join("output");
+    fs::create_dir_all(&output).expect("Failed to create the output directory");
+
+    let filename = output.join(format!("img_{iteration}.png"));
     let mut img = ImageBuffer::new(width as u32, height as u32);
 
     let robots_per_point = collect_robots_per_point(robots);
@@ -192,8 +197,8 @@ fn to_image(robots: &[Robot], width: i64, height: i64, iteration: i64) {
     img.save(filename).expect("Failed to save image");
 }
 
-async fn advanced(file: FileHandle, width: i64, height: i64) -> anyhow::Result<i64> {
-    let mut robots = parse_robots(file);
+fn advanced(robots: &[Robot], width: i64, height: i64) -> AocResult {
+    let mut robots = robots.to_vec();
 
     for iteration in 0..10_000 {
         if iteration % 1000 == 0 {
@@ -208,64 +213,89 @@ async fn advanced(file: FileHandle, width: i64, height: i64) -> anyhow::Result<i
     Ok(-1)
 }
 
-#[tokio::main]
-async fn main() {
-    let file = read_lines("input.txt")
-        .await
-        .expect("Should be able to read input.txt");
+/// Size of the bathroom the robots walk around in, and how long to simulate for part 1.
+pub struct Bathroom {
+    pub width: i64,
+    pub height: i64,
+    pub seconds: i64,
+}
 
-    let _ = dbg!(advanced(file, 101, 103).await);
+impl Default for Bathroom {
+    fn default() -> Self {
+        Self {
+            width: 101,
+            height: 103,
+            seconds: 100,
+        }
+    }
 }
 
-// -- tests --
+pub struct Day14;
 
-type FileHandle = io::Lines<io::BufReader<File>>;
+impl Solution for Day14 {
+    const DAY: u8 = 14;
+    const UNSOLVED: &'static [u8] = &[2];
 
-async fn read_lines<P: AsRef<Path>>(filename: P) -> io::Result<FileHandle> {
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
+    type Input = Vec<Robot>;
+    type Config = Bathroom;
+
+    fn parse(input: &PuzzleInput) -> AocResult<Self::Input> {
+        Ok(parse_robots(input)?)
+    }
+
+    fn part1(input: &Self::Input, config: &Self::Config) -> AocResult<Answer> {
+        Ok(simple(input, config.width, config.height, config.seconds)?.into())
+    }
+
+    fn part2(input: &Self::Input, config: &Self::Config) -> AocResult<Answer> {
+        // writes a frame per second to day_14/output; the tree has to be spotted by hand:
+        advanced(input, config.width, config.height)?;
+        Ok(Answer::Unsolved)
+    }
 }
 
-#[tokio::test]
-async fn test_simple_minimal() {
-    let answer = 12;
+// -- tests --
 
-    let file = read_lines("minimal.txt")
-        .await
-        .expect("Should be able to read minimal.txt");
+#[test]
+fn test_simple_minimal() {
+    let answer = 12;
 
-    assert_eq!(simple(file, 11, 7, 100).await.expect("Oof 1"), answer);
+    let bathroom = Bathroom {
+        width: 11,
+        height: 7,
+        seconds: 100,
+    };
+    let file = aoc_common::input_or_skip!(Day14, "example");
+
+    assert_eq!(
+        Day14::solve_with(1, &file, &bathroom).expect("Oof 1"),
+        answer
+    );
 }
 
-#[tokio::test]
-async fn test_simple() {
+#[test]
+fn test_simple() {
     let answer = 232253028;
 
-    let file = read_lines("input.txt")
-        .await
-        .expect("Should be able to read input.txt");
+    let file = aoc_common::input_or_skip!(Day14, "input");
 
-    assert_eq!(simple(file, 101, 103, 100).await.expect("Oof 1"), answer);
+    assert_eq!(Day14::solve(1, &file).expect("Oof 1"), answer);
 }
 
-// #[tokio::test]
-// async fn test_advanced_minimal() {
+// #[test]
+// fn test_advanced_minimal() {
 //     let answer = 0;
 //
-//     let file = read_lines("minimal.txt")
-//         .await
-//         .expect("Should be able to read minimal.txt");
+//     let file = aoc_common::input_or_skip!(Day14, "example");
 //
-//     assert_eq!(advanced(file).await.expect("Oof 1"), answer);
+//     assert_eq!(Day14::solve(2, &file).expect("Oof 1"), answer);
 // }
 //
-#[tokio::test]
-async fn test_advanced() {
-    let answer = 0; // 8179
+#[test]
+fn test_advanced() {
+    let answer = Answer::Unsolved; // 8179
 
-    let file = read_lines("input.txt")
-        .await
-        .expect("Should be able to read input.txt");
+    let file = aoc_common::input_or_skip!(Day14, "input");
 
-    assert_eq!(advanced(file, 101, 103).await.expect("Oof 2"), answer);
+    assert_eq!(Day14::solve(2, &file).expect("Oof 2"), answer);
 }
@@ -1,111 +1,83 @@
 #![allow(dead_code)]
 
-use std::collections::BTreeMap;
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
+use aoc_common::{Answer, AocResult, Direction, Grid, ParseError, Point, PuzzleInput, Solution};
+use std::fmt::{self, Display};
 
 fn repeat_str(chars: &str, times: usize) -> String {
     (0..times).map(|_| chars).collect()
 }
 
-#[derive(Debug, Hash, Ord, PartialOrd, Eq, PartialEq, Copy, Clone)]
-enum Instruction {
-    Up,
-    Right,
-    Down,
-    Left,
+type Instruction = Direction;
+
+fn parse_instruction(char: &char) -> Result<Instruction, ParseError> {
+    Direction::from_char(*char).ok_or_else(|| ParseError::new(*char, "one of `^>v<`"))
 }
 
-impl Instruction {
-    fn from_char(char: &char) -> Self {
-        match char {
-            '^' => Self::Up,
-            '>' => Self::Right,
-            'v' => Self::Down,
-            '<' => Self::Left,
-            _ => unreachable!("Unsupported instruction"),
+fn test_move(instruction: &Instruction, robot: &Point, map: &Map) -> bool {
+    // returns whether the move is possible
+    // step until empty space or wall:
+    let mut nxt_point: Point = *robot;
+
+    loop {
+        nxt_point += *instruction;
+        let nxt_char = map.get(nxt_point).expect("Out of bounds??");
+
+        match nxt_char.kind {
+            MapEntryType::Empty => return true, // yay
+            MapEntryType::Wall => return false, // aww
+            MapEntryType::Chest => continue,    // do nothing
+            MapEntryType::Robot => {
+                panic!("Two robots??")
+            }
         }
     }
+}
 
-    fn delta(&self) -> Point {
-        match self {
-            Instruction::Up => (-1, 0),
-            Instruction::Right => (0, 1),
-            Instruction::Down => (1, 0),
-            Instruction::Left => (0, -1),
-        }
-    }
+fn apply_move(instruction: &Instruction, robot: &Point, map: &mut Map) -> Point {
+    // applies the move and returns the new robot location
 
-    fn test(&self, robot: &Point, map: &Map) -> bool {
-        // returns whether the move is possible
-        // apply 'delta' until empty space or wall:
-        let delta = self.delta();
+    // #O.OO@ -> #OOO@.
+    // #OOO.@ -> #OOO@.
 
-        let mut nxt_point: Point = *robot;
+    // 'robot' -> .
+    let robot_entry = map
+        .replace(*robot, MapEntry::empty())
+        .expect("There must be a robot here");
 
-        loop {
-            nxt_point = (nxt_point.0 + delta.0, nxt_point.1 + delta.1);
-            let nxt_char = map.get(&nxt_point).expect("Out of bounds??");
+    // 'nxt' -> @
+    let mut nxt_point = *robot + *instruction;
+    let mut nxt_entry = &map
+        .replace(nxt_point, robot_entry)
+        .expect("Must be something here!");
 
-            match nxt_char.kind {
-                MapEntryType::Empty => return true, // yay
-                MapEntryType::Wall => return false, // aww
-                MapEntryType::Chest => continue,    // do nothing
-                MapEntryType::Robot => {
-                    panic!("Two robots??")
-                }
-            }
-        }
-    }
+    let next_robot = nxt_point;
 
-    fn apply(&self, robot: &Point, map: &mut Map) -> Point {
-        // applies the move and returns the new robot location
-        let delta = self.delta();
-
-        // #O.OO@ -> #OOO@.
-        // #OOO.@ -> #OOO@.
-
-        // 'robot' -> .
-        let robot_entry = map
-            .insert(*robot, MapEntry::empty())
-            .expect("There must be a robot here");
-
-        // 'nxt' -> @
-        let mut nxt_point = (robot.0 + delta.0, robot.1 + delta.1);
-        let mut nxt_entry = &map
-            .insert(nxt_point, robot_entry)
-            .expect("Must be something here!");
-
-        let next_robot = nxt_point;
-
-        let mut has_chest = nxt_entry.is_chest();
-        // the rest becomes O
-        while has_chest {
-            nxt_point = (nxt_point.0 + delta.0, nxt_point.1 + delta.1);
-            nxt_entry = map.get(&nxt_point).expect("Should be something here");
-
-            match nxt_entry.kind {
-                MapEntryType::Robot => {
-                    panic!("Two robots??")
-                }
-                MapEntryType::Chest => {
-                    // update position, don't move anything
-                    continue;
-                }
-                MapEntryType::Empty => {
-                    // insert O, stop
-                    map.insert(nxt_point, MapEntry::chest());
-                    has_chest = false;
-                }
-                MapEntryType::Wall => {
-                    has_chest = false;
-                }
+    let mut has_chest = nxt_entry.is_chest();
+    // the rest becomes O
+    while has_chest {
+        nxt_point += *instruction;
+        nxt_entry = map.get(nxt_point).expect("Should be something here");
+
+        match nxt_entry.kind {
+            MapEntryType::Robot => {
+                panic!("Two robots??")
+            }
+            MapEntryType::Chest => {
+                // update position, don't move anything
+                continue;
+            }
+            MapEntryType::Empty => {
+                // insert O, stop
+                map[nxt_point] = MapEntry::chest();
+                has_chest = false;
+            }
+            MapEntryType::Wall => {
+                has_chest = false;
             }
         }
-
-        next_robot
     }
+
+    next_robot
 }
 
 #[derive(Debug, Hash, Ord, PartialOrd, Eq, PartialEq, Copy, Clone)]
@@ -122,19 +94,16 @@ struct MapEntry {
 }
 
 impl MapEntry {
-    fn from_char(char: &char) -> Self {
+    fn from_char(char: &char) -> Result<Self, ParseError> {
         let kind = match char {
             '#' => MapEntryType::Wall,
             'O' => MapEntryType::Chest,
             '@' => MapEntryType::Robot,
             '.' => MapEntryType::Empty,
-            other => {
-                dbg!(other);
-                unreachable!("Unsupported character?")
-            }
+            other => return Err(ParseError::new(*other, "one of `#O@.`")),
         };
 
-        Self { kind }
+        Ok(Self { kind })
     }
 
     fn empty() -> Self {
@@ -185,6 +154,12 @@ impl MapEntry {
     }
 }
 
+impl Display for MapEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_char())
+    }
+}
+
 // impl Into<char> for MapEntry {
 //     fn into(self) -> char {
 //         self.to_char()
@@ -197,22 +172,10 @@ impl MapEntry {
 //     }
 // }
 
-type Point = (i64, i64);
-type Map = BTreeMap<Point, MapEntry>;
+type Map = Grid<MapEntry>;
 
 fn draw(map: &Map) {
-    let mut y = 0;
-    for (point, entry) in map {
-        if point.0 != y {
-            println!();
-            y = point.0;
-        }
-
-        let chr = entry.as_char();
-        print!("{chr}");
-    }
-
-    println!();
+    println!("{map}");
 }
 
 fn draw_fancy(map: &Map) {
@@ -221,25 +184,15 @@ fn draw_fancy(map: &Map) {
 
     sleep(Duration::from_millis(100));
 
-    let mut y = 0;
-    for (point, entry) in map {
-        if point.0 != y {
-            println!();
-            y = point.0;
-        }
-
-        let chr = entry.as_char();
-        print!("{chr}");
-    }
-
-    print!("{}", repeat_str("\n", 10));
+    print!("{map}");
+    print!("{}", repeat_str("\n", 9));
 }
 
 fn gps(map: &Map) -> i64 {
     let mut result = 0;
-    for (point, entry) in map {
+    for (point, entry) in map.iter() {
         if entry.is_chest() {
-            result += point.0 * 100 + point.1;
+            result += point.row * 100 + point.col;
         }
     }
 
@@ -250,37 +203,50 @@ fn count_chests(map: &Map) -> i64 {
     map.iter().filter(|(_, entry)| entry.is_chest()).count() as i64
 }
 
-async fn simple(file: FileHandle) -> anyhow::Result<i64> {
+pub struct Warehouse {
+    map: Map,
+    instructions: Vec<Instruction>,
+    robot: Point,
+}
+
+fn parse_warehouse(input: &PuzzleInput) -> AocResult<Warehouse> {
     let mut instructions_mode = false;
-    let mut map: Map = Default::default();
+    let mut map_lines: Vec<&str> = Default::default();
     let mut instructions: Vec<Instruction> = Default::default();
-    let mut robot: Point = (0, 0);
 
-    for (y, line) in file.map_while(Result::ok).enumerate() {
+    for (idx, line) in input.lines().enumerate() {
         if line.is_empty() {
             instructions_mode = true;
         } else if instructions_mode {
-            for char in line.chars() {
-                instructions.push(Instruction::from_char(&char))
+            for (col, char) in line.chars().enumerate() {
+                instructions.push(parse_instruction(&char).map_err(|err| err.at(idx + 1, col + 1))?)
             }
         } else {
             // map mode
-            for (x, char) in line.chars().enumerate() {
-                let point = (y as i64, x as i64);
-                let entry = MapEntry::from_char(&char);
-                map.insert(point, entry);
-                if entry.is_robot() {
-                    robot = point;
-                }
-            }
+            map_lines.push(line);
         }
     }
 
+    // the map starts on the first line, so its positions are the same as in the file:
+    let map = Grid::try_parse(map_lines, |char| MapEntry::from_char(&char))?;
+    let robot = map.find(&MapEntry::robot()).unwrap_or_default();
+
+    Ok(Warehouse {
+        map,
+        instructions,
+        robot,
+    })
+}
+
+fn simple(warehouse: &Warehouse) -> AocResult {
+    let mut map = warehouse.map.clone();
+    let mut robot = warehouse.robot;
+
     let initial_chest_count = count_chests(&map);
 
-    for instruction in &instructions {
-        if instruction.test(&robot, &map) {
-            robot = instruction.apply(&robot, &mut map);
+    for instruction in &warehouse.instructions {
+        if test_move(instruction, &robot, &map) {
+            robot = apply_move(instruction, &robot, &mut map);
         }
 
         // draw_fancy(&map);
@@ -296,73 +262,71 @@ async fn simple(file: FileHandle) -> anyhow::Result<i64> {
     Ok(gps(&map))
 }
 
-async fn advanced(file: FileHandle) -> anyhow::Result<i64> {
-    for line in file.map_while(Result::ok) {
-        println!("{}", line);
-    }
-    Ok(0)
-}
+pub struct Day15;
 
-// -- tests --
+impl Solution for Day15 {
+    const DAY: u8 = 15;
+    const UNSOLVED: &'static [u8] = &[2];
+
+    type Input = Warehouse;
+    type Config = ();
 
-type FileHandle = io::Lines<io::BufReader<File>>;
+    fn parse(input: &PuzzleInput) -> AocResult<Self::Input> {
+        parse_warehouse(input)
+    }
 
-async fn read_lines<P: AsRef<Path>>(filename: P) -> io::Result<FileHandle> {
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
+    fn part1(input: &Self::Input, _config: &()) -> AocResult<Answer> {
+        Ok(simple(input)?.into())
+    }
+
+    fn part2(_input: &Self::Input, _config: &()) -> AocResult<Answer> {
+        Ok(Answer::Unsolved)
+    }
 }
 
-#[tokio::test]
-async fn test_simple_minimal_1() {
+// -- tests --
+
+#[test]
+fn test_simple_minimal_1() {
     let answer = 2028;
 
-    let file = read_lines("minimal1.txt")
-        .await
-        .expect("Should be able to read minimal1.txt");
+    let file = aoc_common::input_or_skip!(Day15, "example1");
 
-    assert_eq!(simple(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day15::solve(1, &file).expect("Oof 1"), answer);
 }
 
-#[tokio::test]
-async fn test_simple_minimal_2() {
+#[test]
+fn test_simple_minimal_2() {
     let answer = 10092;
 
-    let file = read_lines("minimal2.txt")
-        .await
-        .expect("Should be able to read minimal.txt");
+    let file = aoc_common::input_or_skip!(Day15, "example2");
 
-    assert_eq!(simple(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day15::solve(1, &file).expect("Oof 1"), answer);
 }
 
-#[tokio::test]
-async fn test_simple() {
+#[test]
+fn test_simple() {
     let answer = 1526018;
 
-    let file = read_lines("input.txt")
-        .await
-        .expect("Should be able to read input.txt");
+    let file = aoc_common::input_or_skip!(Day15, "input");
 
-    assert_eq!(simple(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day15::solve(1, &file).expect("Oof 1"), answer);
 }
 
-// #[tokio::test]
-// async fn test_advanced_minimal() {
+// #[test]
+// fn test_advanced_minimal() {
 //     let answer = 0;
 //
-//     let file = read_lines("minimal.txt")
-//         .await
-//         .expect("Should be able to read minimal.txt");
+//     let file = aoc_common::input_or_skip!(Day15, "example");
 //
-//     assert_eq!(advanced(file).await.expect("Oof 1"), answer);
+//     assert_eq!(Day15::solve(2, &file).expect("Oof 1"), answer);
 // }
 //
-// #[tokio::test]
-// async fn test_advanced() {
+// #[test]
+// fn test_advanced() {
 //     let answer = 0;
 //
-//     let file = read_lines("input.txt")
-//         .await
-//         .expect("Should be able to read input.txt");
+//     let file = aoc_common::input_or_skip!(Day15, "input");
 //
-//     assert_eq!(advanced(file).await.expect("Oof 2"), answer);
+//     assert_eq!(Day15::solve(2, &file).expect("Oof 2"), answer);
 // }
@@ -1,9 +1,7 @@
 #![allow(dead_code)]
 
+use aoc_common::{parse_in, Answer, AocResult, ParseError, PuzzleInput, Solution};
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
 
 type Registers = HashMap<char, i64>;
 
@@ -86,7 +84,7 @@ fn run(
             ptr + 2
         }
         _ => {
-            unreachable!("This is a 3 bit computer.")
+            unreachable!("This is a 3 bit computer (checked by `parse_program`).")
         }
     }
 }
@@ -106,65 +104,90 @@ fn process(instructions: &[i64], registers: &mut Registers) -> String {
     results.join(",")
 }
 
-async fn simple(file: FileHandle) -> anyhow::Result<String> {
+type Computer = (Registers, Vec<i64>);
+
+fn parse_register(line: &str) -> Result<(char, i64), ParseError> {
+    let (name, value) = line
+        .strip_prefix("Register ")
+        .and_then(|rest| rest.split_once(":"))
+        .ok_or_else(|| ParseError::in_line(line, line, "a register like `Register A: 729`"))?;
+
+    let register = match name {
+        "A" | "B" | "C" => name.chars().next().unwrap_or_default(),
+        _ => return Err(ParseError::in_line(line, name, "register A, B or C")),
+    };
+    let value: i64 = parse_in(line, value.trim(), "a number")?;
+
+    Ok((register, value))
+}
+
+fn parse_program(line: &str) -> Result<Vec<i64>, ParseError> {
+    let (_, program) = line
+        .split_once(":")
+        .ok_or_else(|| ParseError::in_line(line, line, "a program like `Program: 0,1,5,4`"))?;
+
+    program
+        .trim()
+        .split(",")
+        .map(|it| match parse_in(line, it, "a 3 bit number") {
+            Ok(number @ 0..=7) => Ok(number),
+            Ok(_) => Err(ParseError::in_line(line, it, "a 3 bit number")),
+            Err(err) => Err(err),
+        })
+        .collect()
+}
+
+fn parse_computer(input: &PuzzleInput) -> Result<Computer, ParseError> {
     let mut registers: Registers = Default::default();
     let mut instruction: Vec<i64> = Default::default();
 
-    for line in file.map_while(Result::ok) {
+    for (idx, line) in input.lines().enumerate() {
         if line.is_empty() {
             continue;
         } else if line.starts_with("Register ") {
-            let line = line.strip_prefix("Register ").expect("?");
-            let mut parts = line.split(":");
-            let register = parts
-                .next()
-                .expect("Should have 2 parts")
-                .chars()
-                .next()
-                .expect("Should have at least 1 char");
-            let value: i64 = parts
-                .next()
-                .expect("Should have 2 parts")
-                .trim()
-                .parse()
-                .expect("Should be valid int");
+            let (register, value) = parse_register(line).map_err(|err| err.on_line(idx + 1))?;
 
             registers.insert(register, value);
         } else {
             // program
-            let mut parts = line.split(":");
-            parts.next();
-            let instructions: Vec<i64> = parts
-                .next()
-                .expect("Should have 2 parts")
-                .trim()
-                .split(",")
-                .map(|it| it.parse().expect("Should be valid int"))
-                .collect();
+            let instructions = parse_program(line).map_err(|err| err.on_line(idx + 1))?;
 
             instruction.extend(instructions);
         }
     }
 
-    Ok(process(&instruction, &mut registers))
+    Ok((registers, instruction))
 }
 
-async fn advanced(file: FileHandle) -> anyhow::Result<i64> {
-    for line in file.map_while(Result::ok) {
-        println!("{}", line);
-    }
-    Ok(0)
+fn simple((registers, instructions): &Computer) -> AocResult<String> {
+    let mut registers = registers.clone();
+    Ok(process(instructions, &mut registers))
 }
 
-// -- tests --
+pub struct Day17;
 
-type FileHandle = io::Lines<io::BufReader<File>>;
+impl Solution for Day17 {
+    const DAY: u8 = 17;
+    const UNSOLVED: &'static [u8] = &[2];
 
-async fn read_lines<P: AsRef<Path>>(filename: P) -> io::Result<FileHandle> {
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
+    type Input = Computer;
+    type Config = ();
+
+    fn parse(input: &PuzzleInput) -> AocResult<Self::Input> {
+        Ok(parse_computer(input)?)
+    }
+
+    fn part1(input: &Self::Input, _config: &()) -> AocResult<Answer> {
+        Ok(simple(input)?.into())
+    }
+
+    fn part2(_input: &Self::Input, _config: &()) -> AocResult<Answer> {
+        Ok(Answer::Unsolved)
+    }
 }
 
+// -- tests --
+
 fn empty_registers() -> Registers {
     let mut registers: Registers = Default::default();
     registers.insert('A', 0);
@@ -174,8 +197,8 @@ fn empty_registers() -> Registers {
     registers
 }
 
-#[tokio::test]
-async fn test_instructions() {
+#[test]
+fn test_instructions() {
     // If register C contains 9, the program 2,6 would set register B to 1.
     let mut registers = empty_registers();
     registers.insert('C', 9);
@@ -213,46 +236,38 @@ async fn test_instructions() {
     assert_eq!(registers[&'B'], 44354);
 }
 
-#[tokio::test]
-async fn test_simple_minimal() {
+#[test]
+fn test_simple_minimal() {
     let answer = "4,6,3,5,6,3,5,2,1,0";
 
-    let file = read_lines("minimal.txt")
-        .await
-        .expect("Should be able to read minimal.txt");
+    let file = aoc_common::input_or_skip!(Day17, "example");
 
-    assert_eq!(simple(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day17::solve(1, &file).expect("Oof 1"), answer);
 }
 
-#[tokio::test]
-async fn test_simple() {
+#[test]
+fn test_simple() {
     let answer = "3,6,3,7,0,7,0,3,0";
 
-    let file = read_lines("input.txt")
-        .await
-        .expect("Should be able to read input.txt");
+    let file = aoc_common::input_or_skip!(Day17, "input");
 
-    assert_eq!(simple(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day17::solve(1, &file).expect("Oof 1"), answer);
 }
 
-// #[tokio::test]
-// async fn test_advanced_minimal() {
+// #[test]
+// fn test_advanced_minimal() {
 //     let answer = 0;
 //
-//     let file = read_lines("minimal.txt")
-//         .await
-//         .expect("Should be able to read minimal.txt");
+//     let file = aoc_common::input_or_skip!(Day17, "example");
 //
-//     assert_eq!(advanced(file).await.expect("Oof 1"), answer);
+//     assert_eq!(Day17::solve(2, &file).expect("Oof 1"), answer);
 // }
 //
-// #[tokio::test]
-// async fn test_advanced() {
+// #[test]
+// fn test_advanced() {
 //     let answer = 0;
 //
-//     let file = read_lines("input.txt")
-//         .await
-//         .expect("Should be able to read input.txt");
+//     let file = aoc_common::input_or_skip!(Day17, "input");
 //
-//     assert_eq!(advanced(file).await.expect("Oof 2"), answer);
+//     assert_eq!(Day17::solve(2, &file).expect("Oof 2"), answer);
 // }
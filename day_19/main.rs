@@ -1,9 +1,10 @@
 #![allow(dead_code)]
 
+use aoc_common::{Answer, AocResult, PuzzleInput, Solution};
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 
 // track possibility of goal given previous:
 type Memo = HashMap<(String, String), bool>;
@@ -74,13 +75,15 @@ fn count_matches<S: AsRef<str>>(
     result
 }
 
-fn parse_file(file: FileHandle) -> (Vec<String>, Vec<String>) {
+type Towels = (Vec<String>, Vec<String>);
+
+fn parse_file(input: &PuzzleInput) -> Towels {
     let mut pattern_mode = true;
 
     let mut patterns = vec![];
     let mut goals = vec![];
 
-    for line in file.map_while(Result::ok) {
+    for line in input.lines() {
         if line.is_empty() {
             pattern_mode = false;
         } else if pattern_mode {
@@ -88,102 +91,118 @@ fn parse_file(file: FileHandle) -> (Vec<String>, Vec<String>) {
                 patterns.push(pattern.trim().to_string())
             }
         } else {
-            goals.push(line)
+            goals.push(line.to_string())
         }
     }
 
     (goals, patterns)
 }
 
-async fn simple(file: FileHandle) -> anyhow::Result<i64> {
-    let (goals, patterns) = parse_file(file);
+fn simple((goals, patterns): &Towels) -> AocResult {
+    // each goal has its own memo, so with the `rayon` feature they're checked in parallel:
+    #[cfg(feature = "rayon")]
+    let goals_iter = goals.par_iter();
+    #[cfg(not(feature = "rayon"))]
+    let goals_iter = goals.iter();
 
-    let mut result = 0;
+    let result = goals_iter
+        .enumerate()
+        .filter(|(idx, goal)| {
+            eprintln!("{} / {}", idx + 1, goals.len());
 
-    for (idx, goal) in goals.iter().enumerate() {
-        eprintln!("{} / {}", idx + 1, goals.len());
+            // reset memo per run:
+            let mut memo = Memo::new();
 
-        // reset memo per run:
-        let mut memo = Memo::new();
+            matches(goal, "", patterns, &mut memo)
+        })
+        .count();
 
-        if matches(goal, "", &patterns, &mut memo) {
-            result += 1;
-        }
-    }
+    Ok(result as i64)
+}
+
+fn advanced((goals, patterns): &Towels) -> AocResult {
+    #[cfg(feature = "rayon")]
+    let goals_iter = goals.par_iter();
+    #[cfg(not(feature = "rayon"))]
+    let goals_iter = goals.iter();
+
+    let result = goals_iter
+        .enumerate()
+        .map(|(idx, goal)| {
+            eprintln!("{} / {}", idx + 1, goals.len());
+
+            // reset memo per run:
+            let mut memo = CountMemo::new();
+
+            count_matches(goal, "", patterns, &mut memo)
+        })
+        .sum();
 
     Ok(result)
 }
 
-async fn advanced(file: FileHandle) -> anyhow::Result<i64> {
-    let (goals, patterns) = parse_file(file);
+pub struct Day19;
 
-    let mut result = 0;
+impl Solution for Day19 {
+    const DAY: u8 = 19;
 
-    for (idx, goal) in goals.iter().enumerate() {
-        eprintln!("{} / {}", idx + 1, goals.len());
+    type Input = Towels;
+    type Config = ();
 
-        // reset memo per run:
-        let mut memo = CountMemo::new();
+    fn parse(input: &PuzzleInput) -> AocResult<Self::Input> {
+        Ok(parse_file(input))
+    }
 
-        result += count_matches(goal, "", &patterns, &mut memo);
+    fn part1(input: &Self::Input, _config: &()) -> AocResult<Answer> {
+        Ok(simple(input)?.into())
     }
 
-    Ok(result)
+    fn part2(input: &Self::Input, _config: &()) -> AocResult<Answer> {
+        Ok(advanced(input)?.into())
+    }
 }
 
 // -- tests --
 
-type FileHandle = io::Lines<io::BufReader<File>>;
-
-async fn read_lines<P: AsRef<Path>>(filename: P) -> io::Result<FileHandle> {
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
-}
-
-#[tokio::test]
-async fn test_simple_minimal() {
+#[test]
+fn test_simple_minimal() {
     let answer = 6;
 
-    let file = read_lines("minimal.txt")
-        .await
-        .expect("Should be able to read minimal.txt");
+    let file = aoc_common::input_or_skip!(Day19, "example");
 
-    assert_eq!(simple(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day19::solve(1, &file).expect("Oof 1"), answer);
 }
 
-#[tokio::test]
-async fn test_simple() {
+#[test]
+fn test_simple() {
     let answer = 344;
 
-    let file = read_lines("input.txt")
-        .await
-        .expect("Should be able to read input.txt");
+    let file = aoc_common::input_or_skip!(Day19, "input");
 
-    let result = simple(file).await.expect("Oof 1");
+    let result = Day19::solve(1, &file)
+        .expect("Oof 1")
+        .as_int()
+        .expect("Should be a number");
 
     assert!(result > 341, "too low :( ");
 
     assert_eq!(result, answer);
 }
 
-#[tokio::test]
-async fn test_advanced_minimal() {
+#[test]
+fn test_advanced_minimal() {
     let answer = 16;
 
-    let file = read_lines("minimal.txt")
-        .await
-        .expect("Should be able to read minimal.txt");
+    let file = aoc_common::input_or_skip!(Day19, "example");
 
-    assert_eq!(advanced(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day19::solve(2, &file).expect("Oof 1"), answer);
 }
 
-#[tokio::test]
-async fn test_advanced() {
+#[test]
+fn test_advanced() {
     let answer = 996172272010026;
 
-    let file = read_lines("input.txt")
-        .await
-        .expect("Should be able to read input.txt");
+    let file = aoc_common::input_or_skip!(Day19, "input");
 
-    assert_eq!(advanced(file).await.expect("Oof 2"), answer);
+    assert_eq!(Day19::solve(2, &file).expect("Oof 2"), answer);
 }
@@ -1,133 +1,576 @@
 #![allow(dead_code)]
 extern crate core;
 
-use std::collections::VecDeque;
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
+use aoc_common::{parse_in, Answer, AocResult, Explain, Format, ParseError, PuzzleInput, Solution};
+use serde::Serialize;
+use std::fmt::{self, Display};
 
-fn _line_is_safe(mut parts: VecDeque<i64>) -> bool {
-    let mut prev = parts.pop_front().expect("At least one");
+/// Which way the levels of a report have to go.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Monotonicity {
+    Increasing,
+    Decreasing,
+    /// Either way, as long as it's the same way throughout.
+    Either,
+}
+
+/// When a report counts as safe.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Policy {
+    /// Smallest allowed difference between adjacent levels (in the direction they go).
+    pub min_delta: i64,
+    /// Biggest allowed difference between adjacent levels.
+    pub max_delta: i64,
+    pub monotonicity: Monotonicity,
+    /// How many levels the Problem Dampener may remove in part 2; part 1 never removes any.
+    pub dampener: usize,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Self {
+            min_delta: 1,
+            max_delta: 3,
+            monotonicity: Monotonicity::Either,
+            dampener: 1,
+        }
+    }
+}
+
+impl Policy {
+    fn directions(&self) -> &'static [bool] {
+        // increasing?
+        match self.monotonicity {
+            Monotonicity::Increasing => &[true],
+            Monotonicity::Decreasing => &[false],
+            Monotonicity::Either => &[true, false],
+        }
+    }
+
+    fn step_ok(&self, from: i64, to: i64, increasing: bool) -> bool {
+        let delta = if increasing { to - from } else { from - to };
+        (self.min_delta..=self.max_delta).contains(&delta)
+    }
+}
 
-    let desc = prev > parts[0];
+/// The fewest levels (indices, at most `dampener` of them) to remove to make a report safe,
+/// so an empty list if it's safe as it is, or `None` if it takes more than that.
+pub fn levels_to_remove(levels: &[i64], policy: &Policy, dampener: usize) -> Option<Vec<usize>> {
+    policy
+        .directions()
+        .iter()
+        .filter_map(|&increasing| levels_to_remove_going(levels, policy, dampener, increasing))
+        .min_by_key(Vec::len)
+}
 
-    let range = 1..=3;
+/// `levels_to_remove` for one direction. Whether a level can follow another only depends on those two,
+/// so this keeps the fewest removals needed for a safe run ending at every level. Looking back past
+/// more than `dampener` levels would remove too many, so it's O(n * dampener) instead of trying every subset.
+fn levels_to_remove_going(
+    levels: &[i64],
+    policy: &Policy,
+    dampener: usize,
+    increasing: bool,
+) -> Option<Vec<usize>> {
+    let n = levels.len();
+    // fewest[i]: removals among levels[..i] for a safe run that keeps level i, which follows kept_before[i]:
+    let mut fewest: Vec<Option<usize>> = vec![None; n];
+    let mut kept_before: Vec<Option<usize>> = vec![None; n];
 
-    for rest in parts {
-        if (prev > rest) != desc {
-            return false;
+    for idx in 0..n {
+        if idx <= dampener {
+            // start over here, without anything before
+            fewest[idx] = Some(idx);
         }
-        let delta = (prev - rest).abs();
-        if !range.contains(&delta) {
-            return false;
+
+        for prev in idx.saturating_sub(dampener + 1)..idx {
+            let Some(removed) = fewest[prev] else {
+                continue;
+            };
+            let removed = removed + (idx - prev - 1);
+
+            if removed <= dampener
+                && policy.step_ok(levels[prev], levels[idx], increasing)
+                && fewest[idx].is_none_or(|fewest| removed < fewest)
+            {
+                fewest[idx] = Some(removed);
+                kept_before[idx] = Some(prev);
+            }
         }
+    }
 
-        prev = rest;
+    if n == 0 {
+        return Some(vec![]);
     }
 
-    true
+    // the last kept level, counting the ones after it as removed too:
+    let (last, _) = (0..n)
+        .filter_map(|idx| Some((idx, fewest[idx]? + (n - 1 - idx))))
+        .filter(|&(_, removed)| removed <= dampener)
+        .min_by_key(|&(_, removed)| removed)?;
+
+    let mut kept = vec![false; n];
+    let mut at = Some(last);
+    while let Some(idx) = at {
+        kept[idx] = true;
+        at = kept_before[idx];
+    }
+
+    Some((0..n).filter(|&idx| !kept[idx]).collect())
 }
 
-async fn line_is_safe(line: String) -> bool {
-    let parts: VecDeque<_> = line
-        .split(" ")
-        .map(|it| it.parse::<i64>().unwrap_or_default())
-        .collect();
+/// The first step of a report that breaks the policy, or why it couldn't be read.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum Violation {
+    /// The level at `index` went the other way than the ones before it (or than the policy allows).
+    DirectionChange { index: usize, from: i64, to: i64 },
+    /// The level at `index` is more than `max_delta` away from the one before it.
+    DeltaTooBig { index: usize, from: i64, to: i64 },
+    /// The level at `index` is the same as the one before it.
+    ZeroDelta { index: usize, level: i64 },
+    /// The level at `index` moved, but less than `min_delta`.
+    DeltaTooSmall { index: usize, from: i64, to: i64 },
+    /// The level at `index` isn't a number.
+    ParseError {
+        index: usize,
+        column: usize,
+        text: String,
+    },
+}
 
-    _line_is_safe(parts)
+impl Violation {
+    pub fn index(&self) -> usize {
+        match self {
+            Self::DirectionChange { index, .. }
+            | Self::DeltaTooBig { index, .. }
+            | Self::ZeroDelta { index, .. }
+            | Self::DeltaTooSmall { index, .. }
+            | Self::ParseError { index, .. } => *index,
+        }
+    }
 }
 
-async fn simple(file: FileHandle) -> anyhow::Result<i64> {
-    let mut result = 0;
-    for line in file.map_while(Result::ok) {
-        if line_is_safe(line).await {
-            result += 1;
+impl Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DirectionChange { from, to, .. } => {
+                write!(f, "direction change ({from} -> {to})")
+            }
+            Self::DeltaTooBig { from, to, .. } => write!(f, "delta too big ({from} -> {to})"),
+            Self::ZeroDelta { level, .. } => write!(f, "zero delta ({level} -> {level})"),
+            Self::DeltaTooSmall { from, to, .. } => write!(f, "delta too small ({from} -> {to})"),
+            Self::ParseError { column, text, .. } => {
+                write!(
+                    f,
+                    "parse error (expected a level, found {text:?} at column {column})"
+                )
+            }
         }
     }
-    Ok(result)
 }
 
-async fn line_is_safe_advanced(line: String) -> bool {
-    let parts: VecDeque<_> = line
-        .split(" ")
-        .map(|it| it.parse::<i64>().unwrap_or_default())
-        .collect();
+/// A level that isn't a number, and which level it was.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BadLevel {
+    pub index: usize,
+    pub error: ParseError,
+}
+
+/// One line of the input: its levels, or the first one that isn't a number.
+pub type Report = Result<Vec<i64>, BadLevel>;
+
+/// The first violation in a report as it is, checking every step in order.
+/// With `Monotonicity::Either` the first step that moves at all decides the direction.
+pub fn first_violation(levels: &[i64], policy: &Policy) -> Option<Violation> {
+    let mut increasing = match policy.monotonicity {
+        Monotonicity::Increasing => Some(true),
+        Monotonicity::Decreasing => Some(false),
+        Monotonicity::Either => None,
+    };
 
-    for idx in 0..parts.len() {
-        let mut updated = parts.clone();
-        updated.remove(idx);
+    for (idx, pair) in levels.windows(2).enumerate() {
+        let (from, to, index) = (pair[0], pair[1], idx + 1);
+        let delta = to - from;
 
-        if _line_is_safe(updated) {
-            return true;
+        if delta != 0 && *increasing.get_or_insert(delta > 0) != (delta > 0) {
+            return Some(Violation::DirectionChange { index, from, to });
+        }
+        if delta.abs() > policy.max_delta {
+            return Some(Violation::DeltaTooBig { index, from, to });
+        }
+        if delta.abs() < policy.min_delta {
+            return Some(match delta {
+                0 => Violation::ZeroDelta { index, level: to },
+                _ => Violation::DeltaTooSmall { index, from, to },
+            });
         }
     }
 
-    false
+    None
+}
+
+/// Why a report is (un)safe, as one line of `aoc explain 2`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnosis {
+    /// 1-based.
+    pub line: usize,
+    pub safe: bool,
+    #[serde(flatten)]
+    pub violation: Option<Violation>,
+    /// The levels the Problem Dampener would remove, or `None` if it can't make the report safe.
+    pub dampened: Option<Vec<usize>>,
+}
+
+impl Diagnosis {
+    pub fn new(line: usize, report: &Report, policy: &Policy) -> Self {
+        let (violation, dampened) = match report {
+            Ok(levels) => (
+                first_violation(levels, policy),
+                levels_to_remove(levels, policy, policy.dampener),
+            ),
+            Err(bad) => {
+                let violation = Violation::ParseError {
+                    index: bad.index,
+                    column: bad.error.column,
+                    text: bad.error.text.clone(),
+                };
+                (Some(violation), None)
+            }
+        };
+
+        Self {
+            line,
+            safe: violation.is_none(),
+            violation,
+            dampened,
+        }
+    }
 }
 
-async fn advanced(file: FileHandle) -> anyhow::Result<i64> {
-    let mut result = 0;
-    for line in file.map_while(Result::ok) {
-        if line_is_safe_advanced(line).await {
-            result += 1;
+impl Display for Diagnosis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(violation) = &self.violation else {
+            return write!(f, "line {}: safe", self.line);
+        };
+
+        write!(
+            f,
+            "line {}: unsafe at index {}, {violation}",
+            self.line,
+            violation.index()
+        )?;
+        match &self.dampened {
+            Some(removed) => {
+                let removed: Vec<String> = removed.iter().map(usize::to_string).collect();
+                write!(f, "; safe without index {}", removed.join(", "))
+            }
+            None => write!(f, "; unsafe even with the dampener"),
         }
     }
-    Ok(result)
 }
 
-// -- tests --
+fn parse_report(line: &str, number: usize) -> Report {
+    line.split_whitespace()
+        .enumerate()
+        .map(|(index, level)| {
+            parse_in(line, level, "a level").map_err(|error| BadLevel {
+                index,
+                error: error.on_line(number),
+            })
+        })
+        .collect()
+}
+
+/// Reports that aren't readable are never safe.
+fn count_safe(reports: &[Report], policy: &Policy, dampener: usize) -> i64 {
+    reports
+        .iter()
+        .flatten()
+        .filter(|levels| levels_to_remove(levels, policy, dampener).is_some())
+        .count() as i64
+}
+
+fn simple(reports: &[Report], policy: &Policy) -> AocResult {
+    Ok(count_safe(reports, policy, 0))
+}
 
-type FileHandle = io::Lines<io::BufReader<File>>;
+fn advanced(reports: &[Report], policy: &Policy) -> AocResult {
+    Ok(count_safe(reports, policy, policy.dampener))
+}
+
+pub struct Day2;
+
+impl Solution for Day2 {
+    const DAY: u8 = 2;
+
+    type Input = Vec<Report>;
+    type Config = Policy;
+
+    fn parse(input: &PuzzleInput) -> AocResult<Self::Input> {
+        Ok(input
+            .lines()
+            .enumerate()
+            .map(|(idx, line)| parse_report(line, idx + 1))
+            .collect())
+    }
+
+    fn part1(input: &Self::Input, config: &Self::Config) -> AocResult<Answer> {
+        Ok(simple(input, config)?.into())
+    }
+
+    fn part2(input: &Self::Input, config: &Self::Config) -> AocResult<Answer> {
+        Ok(advanced(input, config)?.into())
+    }
+
+    /// One diagnosis per report; as JSON that's one object per line (JSON lines).
+    fn explain(input: &Self::Input, config: &Self::Config, options: &Explain) -> AocResult<String> {
+        let mut out = String::new();
+        for (idx, report) in input.iter().enumerate() {
+            let diagnosis = Diagnosis::new(idx + 1, report, config);
+            match options.format {
+                Format::Text => out += &diagnosis.to_string(),
+                Format::Json => out += &serde_json::to_string(&diagnosis)?,
+                format => anyhow::bail!("Day {} has no {format} report", Self::DAY),
+            }
+            out.push('\n');
+        }
 
-async fn read_lines<P: AsRef<Path>>(filename: P) -> io::Result<FileHandle> {
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
+        Ok(out)
+    }
 }
 
-#[tokio::test]
-async fn test_simple_minimal() {
+// -- tests --
+
+#[test]
+fn test_simple_minimal() {
     let answer = 2;
 
-    let file = read_lines("minimal.txt")
-        .await
-        .expect("Should be able to read minimal.txt");
+    let file = aoc_common::input_or_skip!(Day2, "example");
 
-    assert_eq!(simple(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day2::solve(1, &file).expect("Oof 1"), answer);
 }
 
-#[tokio::test]
-async fn test_simple() {
+#[test]
+fn test_simple() {
     let answer = 479;
 
-    let file = read_lines("simple.txt")
-        .await
-        .expect("Should be able to read simple.txt");
+    let file = aoc_common::input_or_skip!(Day2, "input");
 
-    assert_eq!(simple(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day2::solve(1, &file).expect("Oof 1"), answer);
 }
 
-#[tokio::test]
-async fn test_advanced_minimal() {
+#[test]
+fn test_advanced_minimal() {
+    let answer = 4;
+
+    let file = aoc_common::input_or_skip!(Day2, "example");
+
+    assert_eq!(Day2::solve(2, &file).expect("Oof 1"), answer);
+}
+
+#[test]
+fn test_advanced_edge_cases() {
     let answer = 14;
 
-    let file = read_lines("advanced.txt")
-        .await
-        .expect("Should be able to read minimal.txt");
+    let file = aoc_common::input_or_skip!(Day2, "edge_cases");
 
-    assert_eq!(advanced(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day2::solve(2, &file).expect("Oof 1"), answer);
 }
 
-#[tokio::test]
-async fn test_advanced() {
+#[test]
+fn test_advanced() {
     let answer = 531;
 
-    let file = read_lines("simple.txt")
-        .await
-        .expect("Should be able to read advanced.txt");
+    let file = aoc_common::input_or_skip!(Day2, "input");
 
-    let result = advanced(file).await.expect("Oof 2");
+    let result = Day2::solve(2, &file)
+        .expect("Oof 2")
+        .as_int()
+        .expect("Should be a number");
 
     dbg!(result);
     assert!(result > 527);
     assert_eq!(result, answer);
 }
+
+#[test]
+fn test_levels_to_remove() {
+    let policy = Policy::default();
+
+    assert_eq!(levels_to_remove(&[7, 6, 4, 2, 1], &policy, 1), Some(vec![]));
+    assert_eq!(levels_to_remove(&[1, 2, 7, 8, 9], &policy, 1), None);
+    // removing the 3 (or the first 4) works just as well:
+    assert_eq!(
+        levels_to_remove(&[1, 3, 2, 4, 5], &policy, 1),
+        Some(vec![2])
+    );
+    assert_eq!(
+        levels_to_remove(&[8, 6, 4, 4, 1], &policy, 1),
+        Some(vec![3])
+    );
+    // the first and last level are candidates too:
+    assert_eq!(levels_to_remove(&[9, 1, 2, 3], &policy, 1), Some(vec![0]));
+    assert_eq!(levels_to_remove(&[1, 2, 3, 9], &policy, 1), Some(vec![3]));
+
+    assert_eq!(
+        levels_to_remove(&[1, 2, 7, 8, 9], &policy, 2),
+        Some(vec![0, 1])
+    );
+    assert_eq!(
+        levels_to_remove(&[1, 9, 9, 2, 3], &policy, 2),
+        Some(vec![1, 2])
+    );
+
+    let decreasing = Policy {
+        monotonicity: Monotonicity::Decreasing,
+        ..Policy::default()
+    };
+    assert_eq!(levels_to_remove(&[1, 3, 2, 4, 5], &decreasing, 1), None);
+    assert_eq!(
+        levels_to_remove(&[7, 6, 4, 2, 1], &decreasing, 0),
+        Some(vec![])
+    );
+
+    let wide = Policy {
+        min_delta: 0,
+        max_delta: 5,
+        ..Policy::default()
+    };
+    assert_eq!(levels_to_remove(&[1, 1, 6, 11], &wide, 0), Some(vec![]));
+}
+
+#[test]
+fn test_levels_to_remove_matches_brute_force() {
+    fn safe(levels: &[i64], policy: &Policy) -> bool {
+        policy.directions().iter().any(|&increasing| {
+            levels
+                .windows(2)
+                .all(|pair| policy.step_ok(pair[0], pair[1], increasing))
+        })
+    }
+
+    // the fewest removals by trying every subset of levels:
+    fn brute_force(levels: &[i64], policy: &Policy, dampener: usize) -> Option<usize> {
+        (0..1u32 << levels.len())
+            .filter(|mask| mask.count_ones() as usize <= dampener)
+            .filter(|mask| {
+                let kept: Vec<i64> = (0..levels.len())
+                    .filter(|idx| mask & (1 << idx) == 0)
+                    .map(|idx| levels[idx])
+                    .collect();
+                safe(&kept, policy)
+            })
+            .map(|mask| mask.count_ones() as usize)
+            .min()
+    }
+
+    let policy = Policy::default();
+    let mut seed = 42u64;
+    for _ in 0..2000 {
+        let len = 1 + (seed % 8) as usize;
+        let levels: Vec<i64> = (0..len)
+            .map(|_| {
+                seed = seed
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                ((seed >> 33) % 10) as i64
+            })
+            .collect();
+
+        assert_eq!(
+            first_violation(&levels, &policy).is_none(),
+            safe(&levels, &policy),
+            "{levels:?}"
+        );
+
+        for dampener in 0..=3 {
+            let removed = levels_to_remove(&levels, &policy, dampener);
+            assert_eq!(
+                removed.as_ref().map(Vec::len),
+                brute_force(&levels, &policy, dampener),
+                "{levels:?} with a dampener of {dampener}"
+            );
+
+            if let Some(removed) = removed {
+                let kept: Vec<i64> = (0..levels.len())
+                    .filter(|idx| !removed.contains(idx))
+                    .map(|idx| levels[idx])
+                    .collect();
+                assert!(safe(&kept, &policy), "{levels:?} without {removed:?}");
+            }
+        }
+    }
+}
+
+#[test]
+fn test_diagnosis() {
+    let input = PuzzleInput::from("7 6 4 2 1\n1 2 7 8 9\n1 3 2 4 5\n8 6 4 4 1\n1 x 3\n");
+    let reports = Day2::parse(&input).expect("Per report");
+    let policy = Policy::default();
+
+    assert_eq!(Day2::part1(&reports, &policy).expect("Oof 1"), 1);
+    assert_eq!(Day2::part2(&reports, &policy).expect("Oof 2"), 3);
+
+    let violations: Vec<Option<Violation>> = reports
+        .iter()
+        .enumerate()
+        .map(|(idx, report)| Diagnosis::new(idx + 1, report, &policy).violation)
+        .collect();
+    assert_eq!(
+        violations,
+        vec![
+            None,
+            Some(Violation::DeltaTooBig {
+                index: 2,
+                from: 2,
+                to: 7
+            }),
+            Some(Violation::DirectionChange {
+                index: 2,
+                from: 3,
+                to: 2
+            }),
+            Some(Violation::ZeroDelta { index: 3, level: 4 }),
+            Some(Violation::ParseError {
+                index: 1,
+                column: 3,
+                text: "x".to_string()
+            }),
+        ]
+    );
+
+    let text = Day2::explain(&reports, &policy, &Format::Text.into()).expect("Explainable");
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines[0], "line 1: safe");
+    assert_eq!(
+        lines[2],
+        "line 3: unsafe at index 2, direction change (3 -> 2); safe without index 2"
+    );
+    assert_eq!(
+        lines[4],
+        r#"line 5: unsafe at index 1, parse error (expected a level, found "x" at column 3); unsafe even with the dampener"#
+    );
+
+    let json = Day2::explain(&reports, &policy, &Format::Json.into()).expect("Explainable");
+    let lines: Vec<&str> = json.lines().collect();
+    assert_eq!(lines.len(), 5);
+    assert_eq!(lines[0], r#"{"line":1,"safe":true,"dampened":[]}"#);
+    assert_eq!(
+        lines[1],
+        r#"{"line":2,"safe":false,"reason":"delta_too_big","index":2,"from":2,"to":7,"dampened":null}"#
+    );
+
+    let increasing = Policy {
+        monotonicity: Monotonicity::Increasing,
+        ..Policy::default()
+    };
+    assert_eq!(
+        first_violation(&[7, 6, 4], &increasing),
+        Some(Violation::DirectionChange {
+            index: 1,
+            from: 7,
+            to: 6
+        })
+    );
+}
@@ -1,8 +1,9 @@
 #![allow(dead_code)]
-use std::fs::File;
-use std::io::{self, BufRead};
+use aoc_common::{parse_in, Answer, AocResult, ParseError, PuzzleInput, Solution};
 use std::ops::BitXor;
-use std::path::Path;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 
 fn prune(secret: i64) -> i64 {
     secret.rem_euclid(16777216)
@@ -39,46 +40,60 @@ fn next_secret(initial: i64, times: i64) -> i64 {
     next
 }
 
-async fn simple(file: FileHandle) -> anyhow::Result<i64> {
-    let mut result = 0;
+fn parse_secrets(input: &PuzzleInput) -> Result<Vec<i64>, ParseError> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| parse_in(line, line, "a number").map_err(|err| err.on_line(idx + 1)))
+        .collect()
+}
+
+fn simple(secrets: &[i64]) -> AocResult {
+    // every buyer is on their own, so with the `rayon` feature they're simulated in parallel:
+    #[cfg(feature = "rayon")]
+    let secrets = secrets.par_iter();
+    #[cfg(not(feature = "rayon"))]
+    let secrets = secrets.iter();
+
+    Ok(secrets.map(|&secret| next_secret(secret, 2000)).sum())
+}
+
+pub struct Day22;
 
-    for line in file.map_while(Result::ok) {
-        let secret: i64 = line.parse().expect("Should be valid number");
+impl Solution for Day22 {
+    const DAY: u8 = 22;
+    const UNSOLVED: &'static [u8] = &[2];
 
-        result += next_secret(secret, 2000);
+    type Input = Vec<i64>;
+    type Config = ();
+
+    fn parse(input: &PuzzleInput) -> AocResult<Self::Input> {
+        Ok(parse_secrets(input)?)
     }
 
-    Ok(result)
-}
+    fn part1(input: &Self::Input, _config: &()) -> AocResult<Answer> {
+        Ok(simple(input)?.into())
+    }
 
-async fn advanced(file: FileHandle) -> anyhow::Result<i64> {
-    for line in file.map_while(Result::ok) {
-        println!("{}", line);
+    fn part2(_input: &Self::Input, _config: &()) -> AocResult<Answer> {
+        Ok(Answer::Unsolved)
     }
-    Ok(0)
 }
 
 // -- tests --
 
-type FileHandle = io::Lines<io::BufReader<File>>;
-
-async fn read_lines<P: AsRef<Path>>(filename: P) -> io::Result<FileHandle> {
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
-}
-
-#[tokio::test]
-async fn test_mix() {
+#[test]
+fn test_mix() {
     assert_eq!(mix(15, 42), 37)
 }
 
-#[tokio::test]
-async fn test_prune() {
+#[test]
+fn test_prune() {
     assert_eq!(prune(100000000), 16113920)
 }
 
-#[tokio::test]
-async fn test_secret_numbers() {
+#[test]
+fn test_secret_numbers() {
     assert_eq!(next_secret(123, 0), 123);
     assert_eq!(next_secret(123, 1), 15887950);
     assert_eq!(next_secret(123, 2), 16495136);
@@ -92,46 +107,38 @@ async fn test_secret_numbers() {
     assert_eq!(next_secret(123, 10), 5908254);
 }
 
-#[tokio::test]
-async fn test_simple_minimal() {
+#[test]
+fn test_simple_minimal() {
     let answer = 37327623;
 
-    let file = read_lines("minimal.txt")
-        .await
-        .expect("Should be able to read minimal.txt");
+    let file = aoc_common::input_or_skip!(Day22, "example");
 
-    assert_eq!(simple(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day22::solve(1, &file).expect("Oof 1"), answer);
 }
 
-#[tokio::test]
-async fn test_simple() {
+#[test]
+fn test_simple() {
     let answer = 0;
 
-    let file = read_lines("input.txt")
-        .await
-        .expect("Should be able to read input.txt");
+    let file = aoc_common::input_or_skip!(Day22, "input");
 
-    assert_eq!(simple(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day22::solve(1, &file).expect("Oof 1"), answer);
 }
 
-// #[tokio::test]
-// async fn test_advanced_minimal() {
+// #[test]
+// fn test_advanced_minimal() {
 //     let answer = 0;
 //
-//     let file = read_lines("minimal.txt")
-//         .await
-//         .expect("Should be able to read minimal.txt");
+//     let file = aoc_common::input_or_skip!(Day22, "example");
 //
-//     assert_eq!(advanced(file).await.expect("Oof 1"), answer);
+//     assert_eq!(Day22::solve(2, &file).expect("Oof 1"), answer);
 // }
 //
-// #[tokio::test]
-// async fn test_advanced() {
+// #[test]
+// fn test_advanced() {
 //     let answer = 0;
 //
-//     let file = read_lines("input.txt")
-//         .await
-//         .expect("Should be able to read input.txt");
+//     let file = aoc_common::input_or_skip!(Day22, "input");
 //
-//     assert_eq!(advanced(file).await.expect("Oof 2"), answer);
+//     assert_eq!(Day22::solve(2, &file).expect("Oof 2"), answer);
 // }
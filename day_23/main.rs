@@ -1,21 +1,17 @@
 #![allow(dead_code)]
 
+use aoc_common::{Answer, AocResult, ParseError, PuzzleInput, Solution};
 use std::collections::{HashMap, HashSet};
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
 
 type Relationships = HashMap<String, HashSet<String>>;
 
-async fn simple(file: FileHandle) -> anyhow::Result<i64> {
-    let empty_hashset: HashSet<String> = HashSet::new();
-
+fn parse_relationships(input: &PuzzleInput) -> Result<Relationships, ParseError> {
     let mut relationships = Relationships::new();
 
-    for line in file.map_while(Result::ok) {
-        let mut parts = line.split("-");
-        let first_node_name = parts.next().expect("Should have 2 parts");
-        let second_node_name = parts.next().expect("Should have 2 parts");
+    for (idx, line) in input.lines().enumerate() {
+        let (first_node_name, second_node_name) = line.split_once("-").ok_or_else(|| {
+            ParseError::in_line(line, line, "two computers like `kh-tc`").on_line(idx + 1)
+        })?;
 
         relationships
             .entry(first_node_name.to_string())
@@ -27,9 +23,15 @@ async fn simple(file: FileHandle) -> anyhow::Result<i64> {
             .insert(first_node_name.to_string());
     }
 
+    Ok(relationships)
+}
+
+fn simple(relationships: &Relationships) -> AocResult {
+    let empty_hashset: HashSet<String> = HashSet::new();
+
     let mut triples = HashSet::new();
 
-    for (source, target) in &relationships {
+    for (source, target) in relationships {
         if !source.starts_with("t") {
             continue;
         }
@@ -55,62 +57,62 @@ async fn simple(file: FileHandle) -> anyhow::Result<i64> {
     Ok(triples.len() as i64)
 }
 
-async fn advanced(file: FileHandle) -> anyhow::Result<i64> {
-    for line in file.map_while(Result::ok) {
-        println!("{}", line);
-    }
-    Ok(0)
-}
+pub struct Day23;
 
-// -- tests --
+impl Solution for Day23 {
+    const DAY: u8 = 23;
+    const UNSOLVED: &'static [u8] = &[2];
+
+    type Input = Relationships;
+    type Config = ();
+
+    fn parse(input: &PuzzleInput) -> AocResult<Self::Input> {
+        Ok(parse_relationships(input)?)
+    }
 
-type FileHandle = io::Lines<io::BufReader<File>>;
+    fn part1(input: &Self::Input, _config: &()) -> AocResult<Answer> {
+        Ok(simple(input)?.into())
+    }
 
-async fn read_lines<P: AsRef<Path>>(filename: P) -> io::Result<FileHandle> {
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
+    fn part2(_input: &Self::Input, _config: &()) -> AocResult<Answer> {
+        Ok(Answer::Unsolved)
+    }
 }
 
-#[tokio::test]
-async fn test_simple_minimal() {
+// -- tests --
+
+#[test]
+fn test_simple_minimal() {
     let answer = 7;
 
-    let file = read_lines("minimal.txt")
-        .await
-        .expect("Should be able to read minimal.txt");
+    let file = aoc_common::input_or_skip!(Day23, "example");
 
-    assert_eq!(simple(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day23::solve(1, &file).expect("Oof 1"), answer);
 }
 
-#[tokio::test]
-async fn test_simple() {
+#[test]
+fn test_simple() {
     let answer = 1327;
 
-    let file = read_lines("input.txt")
-        .await
-        .expect("Should be able to read input.txt");
+    let file = aoc_common::input_or_skip!(Day23, "input");
 
-    assert_eq!(simple(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day23::solve(1, &file).expect("Oof 1"), answer);
 }
 
-// #[tokio::test]
-// async fn test_advanced_minimal() {
+// #[test]
+// fn test_advanced_minimal() {
 //     let answer = 0;
 //
-//     let file = read_lines("minimal.txt")
-//         .await
-//         .expect("Should be able to read minimal.txt");
+//     let file = aoc_common::input_or_skip!(Day23, "example");
 //
-//     assert_eq!(advanced(file).await.expect("Oof 1"), answer);
+//     assert_eq!(Day23::solve(2, &file).expect("Oof 1"), answer);
 // }
 //
-// #[tokio::test]
-// async fn test_advanced() {
+// #[test]
+// fn test_advanced() {
 //     let answer = 0;
 //
-//     let file = read_lines("input.txt")
-//         .await
-//         .expect("Should be able to read input.txt");
+//     let file = aoc_common::input_or_skip!(Day23, "input");
 //
-//     assert_eq!(advanced(file).await.expect("Oof 2"), answer);
+//     assert_eq!(Day23::solve(2, &file).expect("Oof 2"), answer);
 // }
@@ -1,114 +1,577 @@
 #![allow(dead_code)]
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
+use aoc_common::{Answer, AocResult, Explain, Format, PuzzleInput, Solution};
+use serde::Serialize;
+use std::fmt::{self, Display};
+use std::ops::Range;
+
+/// Most digits an operand may have.
+const MAX_DIGITS: usize = 3;
+
+/// The instructions hidden in the corrupted memory. A new one needs a name, an arity, an arm in `Machine::run` and a place in `Op::ALL`, or the lexer never tries it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Op {
+    Mul,
+    Do,
+    Dont,
+}
 
-async fn simple(file: FileHandle) -> anyhow::Result<i64> {
-    let re = regex::Regex::new(r"mul\((\d+?,\d+?)\)")?;
+impl Op {
+    pub const ALL: [Op; 3] = [Op::Mul, Op::Do, Op::Dont];
 
-    let mut lines = String::new();
-    for line in file.map_while(Result::ok) {
-        lines.push_str(&line);
+    pub fn name(self) -> &'static str {
+        match self {
+            Op::Mul => "mul",
+            Op::Do => "do",
+            Op::Dont => "don't",
+        }
     }
 
-    let mut result = 0;
-    for (_, [tuple]) in re.captures_iter(&lines).map(|c| c.extract()) {
-        let mut parts = tuple.split(",");
+    /// How many operands go between the parentheses.
+    pub fn arity(self) -> usize {
+        match self {
+            Op::Mul => 2,
+            Op::Do | Op::Dont => 0,
+        }
+    }
+}
 
-        let left = parts.next().expect("Should have two numeric parts");
-        let right = parts.next().expect("Should have two numeric parts");
+/// A well-formed instruction and where it is in the memory, in bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub op: Op,
+    pub args: Vec<i64>,
+    pub span: Range<usize>,
+}
 
-        let left_no: i64 = left.parse().expect("Should have two numeric parts");
-        let right_no: i64 = right.parse().expect("Should have two numeric parts");
+impl Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let args: Vec<String> = self.args.iter().map(i64::to_string).collect();
+        write!(f, "{}({})", self.op.name(), args.join(","))
+    }
+}
+
+/// How a near-miss falls short of being an instruction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "flaw", rename_all = "snake_case")]
+pub enum Flaw {
+    /// Whitespace around the parentheses or operands, like `mul ( 2 , 4 )`.
+    Whitespace,
+    /// An operand with more than `MAX_DIGITS` digits.
+    LongOperand { digits: usize },
+    /// Something else where `expected` should be, like the `*` in `mul(4*`; `None` at the end of the memory.
+    Unexpected {
+        expected: &'static str,
+        found: Option<char>,
+    },
+}
 
-        result += left_no * right_no;
+impl Display for Flaw {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Flaw::Whitespace => write!(f, "whitespace isn't allowed"),
+            Flaw::LongOperand { digits } => {
+                write!(
+                    f,
+                    "an operand has {digits} digits, at most {MAX_DIGITS} are allowed"
+                )
+            }
+            Flaw::Unexpected {
+                expected,
+                found: Some(found),
+            } => write!(f, "expected {expected}, found {found:?}"),
+            Flaw::Unexpected {
+                expected,
+                found: None,
+            } => write!(f, "expected {expected}, found the end"),
+        }
     }
+}
 
-    Ok(result)
+/// An instruction name and an opening parenthesis that don't make a well-formed instruction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NearMiss {
+    pub text: String,
+    pub span: Range<usize>,
+    #[serde(flatten)]
+    pub flaw: Flaw,
 }
 
-async fn advanced(file: FileHandle) -> anyhow::Result<i64> {
-    let re = regex::Regex::new(r"mul\((\d+?,\d+?)\)")?;
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Lexeme {
+    Instruction(Token),
+    NearMiss(NearMiss),
+}
+
+/// Finds the instructions in a piece of memory one at a time, skipping everything else.
+/// Anything that isn't exactly `name(` operands separated by `,` `)` is corruption, including whitespace
+/// and operands with more than `MAX_DIGITS` digits. Corruption that starts out like an instruction
+/// (a name, maybe some whitespace, and a `(`) comes out as a near-miss.
+pub struct Lexer<'a> {
+    memory: &'a str,
+    pos: usize,
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(memory: &'a str) -> Self {
+        Self { memory, pos: 0 }
+    }
+
+    /// Just the well-formed instructions.
+    pub fn instructions(memory: &'a str) -> impl Iterator<Item = Token> + 'a {
+        Self::new(memory).filter_map(|lexeme| match lexeme {
+            Lexeme::Instruction(token) => Some(token),
+            Lexeme::NearMiss(_) => None,
+        })
+    }
+
+    fn rest(&self, at: usize) -> &'a [u8] {
+        &self.memory.as_bytes()[at..]
+    }
 
-    let mut lines = String::new();
-    for line in file.map_while(Result::ok) {
-        lines.push_str(&line);
+    /// Step over `text` if it comes next.
+    fn eat(&self, at: &mut usize, text: &str) -> bool {
+        let found = self.rest(*at).starts_with(text.as_bytes());
+        if found {
+            *at += text.len();
+        }
+        found
     }
 
-    // split lines on 'do'
-    // throw away regions that start with n't
-    // join again
+    /// Step over whitespace, which is a flaw if there is any.
+    fn skip_whitespace(&self, at: &mut usize, flaw: &mut Option<Flaw>) {
+        let spaces = self
+            .rest(*at)
+            .iter()
+            .take_while(|byte| byte.is_ascii_whitespace())
+            .count();
+        if spaces > 0 {
+            flaw.get_or_insert(Flaw::Whitespace);
+            *at += spaces;
+        }
+    }
 
-    let parts = lines.split("do");
+    /// A near-miss up to and including whatever is at `at` instead of `expected`.
+    fn unexpected(&self, start: usize, at: usize, expected: &'static str) -> Lexeme {
+        let found = self.memory[at..].chars().next();
+        let end = at + found.map_or(0, char::len_utf8);
+        self.near_miss(start..end, Flaw::Unexpected { expected, found })
+    }
 
-    let filtered: Vec<_> = parts.filter(|it| !it.starts_with("n't")).collect();
-    let lines = filtered.join(" ");
+    fn near_miss(&self, span: Range<usize>, flaw: Flaw) -> Lexeme {
+        Lexeme::NearMiss(NearMiss {
+            text: self.memory[span.clone()].to_string(),
+            span,
+            flaw,
+        })
+    }
 
-    let mut result = 0;
-    for (_, [tuple]) in re.captures_iter(&lines).map(|c| c.extract()) {
-        let mut parts = tuple.split(",");
+    /// The instruction `op` starting at `start`, a near-miss if it's malformed there,
+    /// or `None` if it doesn't even get as far as the `(`.
+    fn lex(&self, op: Op, start: usize) -> Option<Lexeme> {
+        let mut at = start;
+        let mut flaw = None;
+        if !self.eat(&mut at, op.name()) {
+            return None;
+        }
+        self.skip_whitespace(&mut at, &mut flaw);
+        if !self.eat(&mut at, "(") {
+            return None;
+        }
+
+        let mut args = Vec::with_capacity(op.arity());
+        for idx in 0..op.arity() {
+            self.skip_whitespace(&mut at, &mut flaw);
+            if idx > 0 {
+                if !self.eat(&mut at, ",") {
+                    return Some(self.unexpected(start, at, "\",\""));
+                }
+                self.skip_whitespace(&mut at, &mut flaw);
+            }
+
+            let digits = self
+                .rest(at)
+                .iter()
+                .take_while(|byte| byte.is_ascii_digit())
+                .count();
+            if digits == 0 {
+                return Some(self.unexpected(start, at, "a number"));
+            }
+            if digits > MAX_DIGITS {
+                flaw.get_or_insert(Flaw::LongOperand { digits });
+            }
+
+            let operand = self.rest(at)[..digits].iter().fold(0i64, |value, digit| {
+                value
+                    .saturating_mul(10)
+                    .saturating_add(i64::from(digit - b'0'))
+            });
+            args.push(operand);
+            at += digits;
+        }
+
+        self.skip_whitespace(&mut at, &mut flaw);
+        if !self.eat(&mut at, ")") {
+            return Some(self.unexpected(start, at, "\")\""));
+        }
+
+        Some(match flaw {
+            None => Lexeme::Instruction(Token {
+                op,
+                args,
+                span: start..at,
+            }),
+            Some(flaw) => self.near_miss(start..at, flaw),
+        })
+    }
+}
 
-        let left = parts.next().expect("Should have two numeric parts");
-        let right = parts.next().expect("Should have two numeric parts");
+impl Iterator for Lexer<'_> {
+    type Item = Lexeme;
+
+    fn next(&mut self) -> Option<Lexeme> {
+        while self.pos < self.memory.len() {
+            let start = self.pos;
+            self.pos += 1;
+
+            let mut near_miss = None;
+            for op in Op::ALL {
+                match self.lex(op, start) {
+                    Some(Lexeme::Instruction(token)) => {
+                        self.pos = token.span.end;
+                        return Some(Lexeme::Instruction(token));
+                    }
+                    Some(lexeme) => near_miss = near_miss.or(Some(lexeme)),
+                    None => {}
+                }
+            }
+
+            // a near-miss can hide a real instruction, like `mul(4*mul(2,3))`, so carry on right after its start:
+            if near_miss.is_some() {
+                return near_miss;
+            }
+        }
+
+        None
+    }
+}
 
-        let left_no: i64 = left.parse().expect("Should have two numeric parts");
-        let right_no: i64 = right.parse().expect("Should have two numeric parts");
+/// Runs instructions in order, keeping track of whether `mul`s are enabled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Machine {
+    /// Whether `do()` and `don't()` do anything (part 2) or are ignored (part 1).
+    pub conditionals: bool,
+    pub enabled: bool,
+    pub total: i64,
+}
 
-        result += left_no * right_no;
+impl Machine {
+    pub fn new(conditionals: bool) -> Self {
+        Self {
+            conditionals,
+            enabled: true,
+            total: 0,
+        }
     }
 
-    Ok(result)
+    /// Run one instruction, returning what it added to the total.
+    pub fn run(&mut self, token: &Token) -> i64 {
+        let contribution = match token.op {
+            Op::Mul if self.enabled => token.args.iter().product(),
+            Op::Mul => 0,
+            Op::Do => {
+                self.enabled |= self.conditionals;
+                0
+            }
+            Op::Dont => {
+                self.enabled &= !self.conditionals;
+                0
+            }
+        };
+
+        self.total += contribution;
+        contribution
+    }
+}
+
+fn instructions(lexemes: &[Lexeme]) -> impl Iterator<Item = &Token> {
+    lexemes.iter().filter_map(|lexeme| match lexeme {
+        Lexeme::Instruction(token) => Some(token),
+        Lexeme::NearMiss(_) => None,
+    })
+}
+
+fn execute(lexemes: &[Lexeme], conditionals: bool) -> i64 {
+    let mut machine = Machine::new(conditionals);
+    for token in instructions(lexemes) {
+        machine.run(token);
+    }
+
+    machine.total
+}
+
+fn simple(lexemes: &[Lexeme]) -> AocResult {
+    Ok(execute(lexemes, false))
+}
+
+fn advanced(lexemes: &[Lexeme]) -> AocResult {
+    Ok(execute(lexemes, true))
+}
+
+/// One instruction as part 2 ran it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Step {
+    pub instruction: String,
+    pub span: Range<usize>,
+    /// Whether `mul`s were enabled when it ran.
+    pub enabled: bool,
+    pub contribution: i64,
+}
+
+/// Every instruction that was found in order, and separately everything that nearly was one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Trace {
+    pub steps: Vec<Step>,
+    pub near_misses: Vec<NearMiss>,
+    pub part1: i64,
+    pub part2: i64,
+}
+
+impl Trace {
+    pub fn new(lexemes: &[Lexeme]) -> Self {
+        let mut machine = Machine::new(true);
+        let steps = instructions(lexemes)
+            .map(|token| {
+                let enabled = machine.enabled;
+                Step {
+                    instruction: token.to_string(),
+                    span: token.span.clone(),
+                    enabled,
+                    contribution: machine.run(token),
+                }
+            })
+            .collect();
+
+        let near_misses = lexemes
+            .iter()
+            .filter_map(|lexeme| match lexeme {
+                Lexeme::NearMiss(near_miss) => Some(near_miss.clone()),
+                Lexeme::Instruction(_) => None,
+            })
+            .collect();
+
+        Self {
+            steps,
+            near_misses,
+            part1: execute(lexemes, false),
+            part2: machine.total,
+        }
+    }
+}
+
+impl Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "instructions (offset, enabled, contribution):")?;
+        for step in &self.steps {
+            let enabled = if step.enabled { "on" } else { "off" };
+            writeln!(
+                f,
+                "  {:>6}..{:<6} {:<3} {:>8}  {}",
+                step.span.start, step.span.end, enabled, step.contribution, step.instruction
+            )?;
+        }
+
+        writeln!(f, "near misses:")?;
+        for near_miss in &self.near_misses {
+            writeln!(
+                f,
+                "  {:>6}..{:<6} {:?}: {}",
+                near_miss.span.start, near_miss.span.end, near_miss.text, near_miss.flaw
+            )?;
+        }
+
+        writeln!(f, "part 1: {}", self.part1)?;
+        writeln!(f, "part 2: {}", self.part2)
+    }
+}
+
+pub struct Day3;
+
+impl Solution for Day3 {
+    const DAY: u8 = 3;
+
+    type Input = Vec<Lexeme>;
+    type Config = ();
+
+    fn parse(input: &PuzzleInput) -> AocResult<Self::Input> {
+        Ok(Lexer::new(input.text()).collect())
+    }
+
+    fn part1(input: &Self::Input, _config: &()) -> AocResult<Answer> {
+        Ok(simple(input)?.into())
+    }
+
+    fn part2(input: &Self::Input, _config: &()) -> AocResult<Answer> {
+        Ok(advanced(input)?.into())
+    }
+
+    fn explain(input: &Self::Input, _config: &(), options: &Explain) -> AocResult<String> {
+        let trace = Trace::new(input);
+
+        Ok(match options.format {
+            Format::Text => trace.to_string(),
+            Format::Json => serde_json::to_string_pretty(&trace)? + "\n",
+            format => anyhow::bail!("Day {} has no {format} report", Self::DAY),
+        })
+    }
 }
 
 // -- tests --
 
-type FileHandle = io::Lines<io::BufReader<File>>;
+#[test]
+fn test_inline_example() {
+    let input = PuzzleInput::from(
+        "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))",
+    );
 
-async fn read_lines<P: AsRef<Path>>(filename: P) -> io::Result<FileHandle> {
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
+    assert_eq!(Day3::solve(2, &input).expect("Oof 2"), 48);
 }
 
-#[tokio::test]
-async fn test_simple_minimal() {
+#[test]
+fn test_simple_minimal() {
     let answer = 161;
 
-    let file = read_lines("minimal.txt")
-        .await
-        .expect("Should be able to read minimal.txt");
+    let file = aoc_common::input_or_skip!(Day3, "example");
 
-    assert_eq!(simple(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day3::solve(1, &file).expect("Oof 1"), answer);
 }
 
-#[tokio::test]
-async fn test_simple() {
+#[test]
+fn test_simple() {
     let answer = 192767529;
 
-    let file = read_lines("simple.txt")
-        .await
-        .expect("Should be able to read simple.txt");
+    let file = aoc_common::input_or_skip!(Day3, "input");
 
-    assert_eq!(simple(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day3::solve(1, &file).expect("Oof 1"), answer);
 }
 
-#[tokio::test]
-async fn test_advanced_minimal() {
+#[test]
+fn test_advanced_minimal() {
     let answer = 48;
 
-    let file = read_lines("advanced.txt")
-        .await
-        .expect("Should be able to read advanced.txt");
+    let file = aoc_common::input_or_skip!(Day3, "example2");
 
-    assert_eq!(advanced(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day3::solve(2, &file).expect("Oof 1"), answer);
 }
 
-#[tokio::test]
-async fn test_advanced() {
+#[test]
+fn test_advanced() {
     let answer = 104083373;
 
-    let file = read_lines("simple.txt")
-        .await
-        .expect("Should be able to read simple.txt");
+    let file = aoc_common::input_or_skip!(Day3, "input");
+
+    assert_eq!(Day3::solve(2, &file).expect("Oof 2"), answer);
+}
+
+#[test]
+fn test_lexer() {
+    let tokens: Vec<Token> = Lexer::instructions(
+        "mul(1,22)do()mul(1234,5)mul ( 2 , 4 )don't()mul(3,\n4)mul(4*mul(7,333)",
+    )
+    .collect();
+    let ops: Vec<(Op, Vec<i64>)> = tokens
+        .iter()
+        .map(|token| (token.op, token.args.clone()))
+        .collect();
+
+    assert_eq!(
+        ops,
+        vec![
+            (Op::Mul, vec![1, 22]),
+            (Op::Do, vec![]),
+            (Op::Dont, vec![]),
+            (Op::Mul, vec![7, 333]),
+        ]
+    );
+    assert_eq!(tokens[0].span, 0..9);
+    assert_eq!(tokens[1].span, 9..13);
+}
+
+#[test]
+fn test_do_in_other_text() {
+    // the old approach split on "do", so "undone" turned mul(2,3) off:
+    let input = PuzzleInput::from("mul(2,3)undone mul(4,5)don't()mul(6,7)\ndo()mul(1,1)");
+
+    assert_eq!(Day3::solve(1, &input).expect("Oof 1"), 6 + 20 + 42 + 1);
+    assert_eq!(Day3::solve(2, &input).expect("Oof 2"), 6 + 20 + 1);
+}
 
-    assert_eq!(advanced(file).await.expect("Oof 2"), answer);
+#[test]
+fn test_trace() {
+    let input =
+        PuzzleInput::from("xmul(2,4)&mul(4*don't()_mul ( 2 , 4 )mul(5,5)+mul(1234,5)do()mul(8,5)");
+    let trace = Trace::new(&Day3::parse(&input).expect("Lexable"));
+
+    let steps: Vec<(&str, usize, bool, i64)> = trace
+        .steps
+        .iter()
+        .map(|step| {
+            (
+                step.instruction.as_str(),
+                step.span.start,
+                step.enabled,
+                step.contribution,
+            )
+        })
+        .collect();
+    assert_eq!(
+        steps,
+        vec![
+            ("mul(2,4)", 1, true, 8),
+            ("don't()", 16, true, 0),
+            ("mul(5,5)", 37, false, 0),
+            ("do()", 57, false, 0),
+            ("mul(8,5)", 61, true, 40),
+        ]
+    );
+    assert_eq!((trace.part1, trace.part2), (73, 48));
+
+    let near_misses: Vec<(&str, &Flaw)> = trace
+        .near_misses
+        .iter()
+        .map(|near_miss| (near_miss.text.as_str(), &near_miss.flaw))
+        .collect();
+    assert_eq!(
+        near_misses,
+        vec![
+            (
+                "mul(4*",
+                &Flaw::Unexpected {
+                    expected: "\",\"",
+                    found: Some('*')
+                }
+            ),
+            ("mul ( 2 , 4 )", &Flaw::Whitespace),
+            ("mul(1234,5)", &Flaw::LongOperand { digits: 4 }),
+        ]
+    );
+    assert_eq!(trace.near_misses[1].span, 24..37);
+
+    let text = Day3::explain(
+        &Day3::parse(&input).expect("Lexable"),
+        &(),
+        &Format::Text.into(),
+    )
+    .expect("Explainable");
+    assert!(
+        text.contains(r#"mul(4*": expected ",", found '*'"#),
+        "{text}"
+    );
+
+    let json = Day3::explain(
+        &Day3::parse(&input).expect("Lexable"),
+        &(),
+        &Format::Json.into(),
+    )
+    .expect("Explainable");
+    assert!(json.contains(r#""flaw": "long_operand""#), "{json}");
 }
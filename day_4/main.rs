@@ -1,191 +1,250 @@
 #![allow(dead_code)]
 
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
+use aoc_common::{Answer, AocResult, Explain, Format, Grid, ParseError, PuzzleInput, Solution};
+use std::path::{Path, PathBuf};
 
-type Coordinate = (isize, isize);
-type Matrix = HashMap<Coordinate, char>;
+mod render;
+mod search;
 
-const TARGET: &str = "XMAS";
+pub use render::{render, save_png};
+pub use search::{find_all, Match, Pattern, Query, Symmetry};
 
-fn char_at<'matrix>(matrix: &'matrix Matrix, coord: &Coordinate) -> &'matrix char {
-    matrix.get(coord).unwrap_or(&'_')
-}
+type Matrix = Grid<char>;
 
-fn try_find_xmas(coordinate: &Coordinate, matrix: &Matrix, d_x: isize, d_y: isize) -> bool {
-    let mut coord = *coordinate;
+/// What each part looks for, so a new variant of the puzzle is a different config.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordSearch {
+    pub part1: Query,
+    pub part2: Query,
+}
 
-    for char in TARGET.chars() {
-        if char_at(matrix, &coord) != &char {
-            return false;
+impl Default for WordSearch {
+    fn default() -> Self {
+        Self {
+            part1: Query::Word("XMAS".to_string()),
+            // two MAS on the diagonals, either way round:
+            part2: Query::Template {
+                rows: "M.S\n.A.\nM.S".to_string(),
+                wildcard: '.',
+                symmetry: Symmetry::Rotations,
+            },
         }
-
-        coord = (coord.0 + d_x, coord.1 + d_y)
     }
+}
 
-    true
+fn parse_matrix(input: &PuzzleInput) -> Result<Matrix, ParseError> {
+    Grid::chars(input.lines())
 }
 
-fn try_find_mas(coordinate: &Coordinate, matrix: &Matrix, d_x: isize, d_y: isize) -> bool {
-    // 1 check that coord is A
-    // 2 check that coord +dx,dy = M
-    // 3 check that coord -dx,dy = S
-    let mut coord = *coordinate;
+/// Every match of `query` in `matrix`.
+pub fn matches(matrix: &Matrix, query: &Query) -> Result<Vec<Match>, ParseError> {
+    Ok(find_all(matrix, &query.patterns()?))
+}
 
-    // 1
-    if char_at(matrix, &coord) != &'A' {
-        return false;
-    }
+fn count(matrix: &Matrix, query: &Query) -> AocResult {
+    Ok(matches(matrix, query)?.len() as i64)
+}
 
-    // 2
-    coord = (coordinate.0 + d_x, coordinate.1 + d_y);
-    if char_at(matrix, &coord) != &'M' {
-        return false;
-    }
+/// How many pixels wide and high a cell is in the PNGs `explain` draws.
+const PNG_SCALE: u32 = 8;
 
-    // 3
-    coord = (coordinate.0 - d_x, coordinate.1 - d_y);
-    if char_at(matrix, &coord) != &'S' {
-        return false;
-    }
+/// Where `explain` draws one part's PNG: `grid.png` becomes `grid_part1.png` for part 1.
+fn png_path(path: &Path, part: &str) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!("{stem}_{part}.png"))
+}
 
-    true
+fn simple(matrix: &Matrix, config: &WordSearch) -> AocResult {
+    count(matrix, &config.part1)
 }
 
-fn try_find_mas_x(coordinate: &Coordinate, matrix: &Matrix) -> bool {
-    // find MAS on one diagonal AND on the other
-    let mas_1 = try_find_mas(coordinate, matrix, -1, -1) | try_find_mas(coordinate, matrix, 1, 1);
-    let mas_2 = try_find_mas(coordinate, matrix, -1, 1) | try_find_mas(coordinate, matrix, 1, -1);
+fn advanced(matrix: &Matrix, config: &WordSearch) -> AocResult {
+    count(matrix, &config.part2)
+}
 
-    // find MAS on horizontal and vertical:
-    // let mas_3 = try_find_mas(coordinate, matrix, -1, 0) | try_find_mas(coordinate, matrix, 1, 0);
-    // let mas_4 = try_find_mas(coordinate, matrix, 0, -1) | try_find_mas(coordinate, matrix, 0, 1);
+pub struct Day4;
 
-    mas_1 & mas_2 // | (mas_3 & mas_4)
-}
+impl Solution for Day4 {
+    const DAY: u8 = 4;
 
-async fn simple(file: FileHandle) -> anyhow::Result<i64> {
-    let mut x_es: Vec<Coordinate> = vec![];
-    let mut matrix: Matrix = HashMap::new();
+    type Input = Matrix;
+    type Config = WordSearch;
 
-    for (x_idx, line) in file.map_while(Result::ok).enumerate() {
-        for (y_index, char) in line.chars().enumerate() {
-            let key = (x_idx as isize, y_index as isize);
-            matrix.insert(key, char);
-            if char == 'X' {
-                x_es.push(key);
-            }
-        }
+    fn parse(input: &PuzzleInput) -> AocResult<Self::Input> {
+        Ok(parse_matrix(input)?)
     }
 
-    let mut score = 0;
-    for coordinate in &x_es {
-        for d_x in -1..=1 {
-            for d_y in -1..=1 {
-                if try_find_xmas(coordinate, &matrix, d_x, d_y) {
-                    score += 1
-                }
-            }
-        }
+    fn part1(input: &Self::Input, config: &Self::Config) -> AocResult<Answer> {
+        Ok(simple(input, config)?.into())
     }
 
-    Ok(score)
-}
-
-async fn advanced(file: FileHandle) -> anyhow::Result<i64> {
-    let mut a_coords: Vec<Coordinate> = vec![];
-    let mut matrix: Matrix = HashMap::new();
+    fn part2(input: &Self::Input, config: &Self::Config) -> AocResult<Answer> {
+        Ok(advanced(input, config)?.into())
+    }
 
-    for (x_idx, line) in file.map_while(Result::ok).enumerate() {
-        for (y_index, char) in line.chars().enumerate() {
-            let key = (x_idx as isize, y_index as isize);
-            matrix.insert(key, char);
-            if char == 'A' {
-                a_coords.push(key);
+    /// Both parts' matches, drawn like the puzzle does, in color if asked (or the cells of every match, as JSON).
+    /// With a PNG path, each part is also drawn as an image next to it, see `png_path`.
+    fn explain(input: &Self::Input, config: &Self::Config, options: &Explain) -> AocResult<String> {
+        let parts = [
+            ("part1", matches(input, &config.part1)?),
+            ("part2", matches(input, &config.part2)?),
+        ];
+
+        if let Some(path) = &options.png {
+            for (part, found) in &parts {
+                save_png(input, found, &png_path(path, part), PNG_SCALE)?;
             }
         }
-    }
 
-    let mut score = 0;
-    for coordinate in &a_coords {
-        if try_find_mas_x(coordinate, &matrix) {
-            score += 1
-        }
+        Ok(match options.format {
+            Format::Text => parts
+                .iter()
+                .map(|(part, found)| {
+                    format!(
+                        "{part}: {} matches\n{}",
+                        found.len(),
+                        render(input, found, options.color)
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Format::Json => {
+                let cells = |found: &[Match]| -> Vec<Vec<(i64, i64)>> {
+                    found
+                        .iter()
+                        .map(|found| {
+                            found
+                                .cells
+                                .iter()
+                                .map(|point| (point.row, point.col))
+                                .collect()
+                        })
+                        .collect()
+                };
+                let json = serde_json::json!({
+                    "part1": cells(&parts[0].1),
+                    "part2": cells(&parts[1].1),
+                });
+                serde_json::to_string_pretty(&json)? + "\n"
+            }
+            format => anyhow::bail!("Day {} has no {format} report", Self::DAY),
+        })
     }
-
-    Ok(score)
 }
 
 // -- tests --
 
-type FileHandle = io::Lines<io::BufReader<File>>;
-
-async fn read_lines<P: AsRef<Path>>(filename: P) -> io::Result<FileHandle> {
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
-}
-
-#[tokio::test]
-async fn test_simple_minimal() {
+#[test]
+fn test_simple_minimal() {
     let answer = 18;
 
-    let file = read_lines("minimal.txt")
-        .await
-        .expect("Should be able to read minimal.txt");
+    let file = aoc_common::input_or_skip!(Day4, "example");
 
-    assert_eq!(simple(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day4::solve(1, &file).expect("Oof 1"), answer);
 }
 
-#[tokio::test]
-async fn test_simple() {
+#[test]
+fn test_simple() {
     let answer = 2390;
 
-    let file = read_lines("simple.txt")
-        .await
-        .expect("Should be able to read simple.txt");
+    let file = aoc_common::input_or_skip!(Day4, "input");
 
-    assert_eq!(simple(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day4::solve(1, &file).expect("Oof 1"), answer);
 }
 
-#[tokio::test]
-async fn test_advanced_minimal() {
+#[test]
+fn test_advanced_minimal() {
     let answer = 9;
 
-    let file = read_lines("minimal.txt")
-        .await
-        .expect("Should be able to read minimal.txt");
+    let file = aoc_common::input_or_skip!(Day4, "example");
 
-    assert_eq!(advanced(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day4::solve(2, &file).expect("Oof 1"), answer);
 }
 
-// #[tokio::test]
-// async fn test_advanced_shape2() {
-//     // with MAS in a + shape instead of x
-//     let answer = 1;
-//
-//     let file = read_lines("shape_two.txt")
-//         .await
-//         .expect("Should be able to read minimal.txt");
-//
-//     assert_eq!(advanced(file).await.expect("Oof 1"), answer);
-// }
-
-#[tokio::test]
-async fn test_advanced() {
+#[test]
+fn test_advanced() {
     let answer = 1809;
 
-    let file = read_lines("simple.txt")
-        .await
-        .expect("Should be able to read advanced.txt");
-
-    let result = advanced(file).await.expect("Oof 2");
+    let file = aoc_common::input_or_skip!(Day4, "input");
 
-    dbg!(result);
+    assert_eq!(Day4::solve(2, &file).expect("Oof 2"), answer);
+}
 
-    assert!(result > 441); // 441 too low
-    assert!(result < 1840); // 1840 too high
+#[test]
+fn test_other_shapes() {
+    let input = PuzzleInput::from(".M.S\nMAS.\n.S..\n");
+    let matrix = Day4::parse(&input).expect("Rectangular");
+
+    // the + shaped variant is just a different template:
+    let plus = WordSearch {
+        part1: Query::Word("MAS".to_string()),
+        part2: Query::Template {
+            rows: ".M.\nMAS\n.S.".to_string(),
+            wildcard: '.',
+            symmetry: Symmetry::RotationsAndReflections,
+        },
+    };
+    assert_eq!(Day4::solve_with(1, &input, &plus).expect("Oof 1"), 2);
+    assert_eq!(Day4::solve_with(2, &input, &plus).expect("Oof 2"), 1);
+
+    let found = matches(&matrix, &plus.part2).expect("A template");
+    assert_eq!(found[0].cells.len(), 5);
+}
 
-    assert_eq!(result, answer);
+#[test]
+fn test_explain() {
+    let file = aoc_common::input_or_skip!(Day4, "example");
+    let matrix = Day4::parse(&file).expect("Rectangular");
+
+    let found = matches(&matrix, &WordSearch::default().part1).expect("A word");
+    assert_eq!(
+        render(&matrix, &found, false),
+        "\
+....XXMAS.
+.SAMXMS...
+...S..A...
+..A.A.MS.X
+XMASAMX.MM
+X.....XA.A
+S.S.S.S.SS
+.A.A.A.A.A
+..M.M.M.MM
+.X.X.XMASX
+"
+    );
+
+    let text =
+        Day4::explain(&matrix, &WordSearch::default(), &Format::Text.into()).expect("Explainable");
+    assert!(text.starts_with("part1: 18 matches\n"));
+    assert!(text.contains("part2: 9 matches\n.M.S......\n"), "{text}");
+
+    let dir = std::env::temp_dir().join(format!("day_4_explain_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("Temp dir");
+    let options = Explain {
+        color: true,
+        png: Some(dir.join("grid.png")),
+        ..Default::default()
+    };
+    let colored = Day4::explain(&matrix, &WordSearch::default(), &options).expect("Explainable");
+    // the first match found, XMAS at (0, 5), is in the first color:
+    let first: String = "XMAS"
+        .chars()
+        .map(|letter| format!("\x1b[1;31m{letter}\x1b[0m"))
+        .collect();
+    assert!(
+        colored.starts_with(&format!(
+            "part1: 18 matches\n....\x1b[1;34mX\x1b[0m{first}.\n"
+        )),
+        "{colored:?}"
+    );
+    assert_eq!(
+        colored.replace("\x1b[0m", "").matches("\x1b[1;").count(),
+        text.chars().filter(char::is_ascii_uppercase).count()
+    );
+
+    for part in ["part1", "part2"] {
+        let img = image::open(dir.join(format!("grid_{part}.png"))).expect("Drawn");
+        assert_eq!(img.width(), 10 * PNG_SCALE);
+    }
+    let _ = std::fs::remove_dir_all(&dir);
 }
@@ -0,0 +1,108 @@
+use crate::Match;
+use anyhow::Context;
+use aoc_common::{AocResult, Grid, Point};
+use image::{ImageBuffer, Rgb};
+use std::fmt::Write;
+use std::path::Path;
+
+/// The colors matches take turns with, as an ANSI foreground code and as RGB.
+const PALETTE: [(u8, [u8; 3]); 6] = [
+    (31, [220, 50, 47]),
+    (32, [133, 153, 0]),
+    (33, [181, 137, 0]),
+    (34, [38, 139, 210]),
+    (35, [211, 54, 130]),
+    (36, [42, 161, 152]),
+];
+
+/// Cells that aren't part of any match, in a PNG.
+const BACKGROUND: [u8; 3] = [32, 32, 32];
+
+/// For every cell, the first match it's part of.
+fn owners(grid: &Grid<char>, matches: &[Match]) -> Grid<Option<usize>> {
+    let mut owners = grid.map(|_| None);
+    for (idx, found) in matches.iter().enumerate() {
+        for &point in &found.cells {
+            if let Some(owner @ None) = owners.get_mut(point) {
+                *owner = Some(idx);
+            }
+        }
+    }
+
+    owners
+}
+
+/// The grid like the puzzle draws it: matched letters stay, everything else becomes a `.`.
+/// With `color`, every match gets its own ANSI color (a letter shared by several takes the first one's).
+pub fn render(grid: &Grid<char>, matches: &[Match], color: bool) -> String {
+    let owners = owners(grid, matches);
+
+    let mut out = String::new();
+    for (point, owner) in owners.iter() {
+        let letter = grid[point];
+        match owner {
+            None => out.push('.'),
+            Some(idx) if color => {
+                let (code, _) = PALETTE[idx % PALETTE.len()];
+                let _ = write!(out, "\x1b[1;{code}m{letter}\x1b[0m");
+            }
+            Some(_) => out.push(letter),
+        }
+
+        if point.col as usize == grid.width() - 1 {
+            out.push('\n');
+        }
+    }
+
+    out
+}
+
+/// Save the matches as a PNG, with every cell a `scale` by `scale` square in its match's color.
+pub fn save_png(grid: &Grid<char>, matches: &[Match], path: &Path, scale: u32) -> AocResult<()> {
+    let owners = owners(grid, matches);
+    let (width, height) = (grid.width() as u32, grid.height() as u32);
+
+    let img = ImageBuffer::from_fn(width * scale, height * scale, |x, y| {
+        let point = Point::new((y / scale) as i64, (x / scale) as i64);
+        match owners[point] {
+            Some(idx) => Rgb(PALETTE[idx % PALETTE.len()].1),
+            None => Rgb(BACKGROUND),
+        }
+    });
+
+    img.save(path)
+        .with_context(|| format!("Can't write {}", path.display()))
+}
+
+// -- tests --
+
+#[test]
+fn test_render() {
+    let grid = Grid::chars(["XMAS", "AMXS"]).expect("Rectangular");
+    let matches = vec![
+        Match {
+            pattern: 0,
+            cells: vec![Point::new(0, 0), Point::new(0, 1), Point::new(0, 2)],
+        },
+        Match {
+            pattern: 1,
+            cells: vec![Point::new(0, 2), Point::new(1, 3)],
+        },
+    ];
+
+    assert_eq!(render(&grid, &matches, false), "XMA.\n...S\n");
+    assert_eq!(
+        render(&grid, &matches[1..], true),
+        "..\x1b[1;31mA\x1b[0m.\n...\x1b[1;31mS\x1b[0m\n"
+    );
+    assert_eq!(render(&grid, &[], false), "....\n....\n");
+
+    let path = std::env::temp_dir().join(format!("day_4_{}.png", std::process::id()));
+    save_png(&grid, &matches, &path, 3).expect("Writable");
+    let img = image::open(&path).expect("Readable").to_rgb8();
+    assert_eq!(img.dimensions(), (12, 6));
+    assert_eq!(img.get_pixel(0, 0).0, PALETTE[0].1);
+    assert_eq!(img.get_pixel(11, 5).0, PALETTE[1].1);
+    assert_eq!(img.get_pixel(11, 0).0, BACKGROUND);
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,260 @@
+use aoc_common::{Direction, Grid, ParseError, Point};
+use std::collections::HashSet;
+
+/// Which orientations of a template count.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Symmetry {
+    /// Only the template as written.
+    Fixed,
+    /// Turned by any multiple of 90 degrees.
+    Rotations,
+    /// Turned, mirrored, or both.
+    RotationsAndReflections,
+}
+
+/// Letters at fixed offsets from each other; anything in between is a wildcard.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    /// Offsets from the first letter, which is the anchor the search starts from.
+    cells: Vec<(Point, char)>,
+}
+
+impl Pattern {
+    /// `cells` in the order matches should report them in.
+    fn new(cells: Vec<(Point, char)>) -> Self {
+        let anchor = cells.first().map_or(Point::ORIGIN, |&(offset, _)| offset);
+        let cells = cells
+            .into_iter()
+            .map(|(offset, letter)| (offset - anchor, letter))
+            .collect();
+
+        Self { cells }
+    }
+
+    /// A word written in `direction`.
+    pub fn word(word: &str, direction: Direction) -> Self {
+        let cells = word
+            .chars()
+            .enumerate()
+            .map(|(idx, letter)| (direction.delta() * idx as i64, letter))
+            .collect();
+
+        Self::new(cells)
+    }
+
+    /// A 2D template, one row per line, where `wildcard` matches any letter.
+    pub fn template(rows: &str, wildcard: char) -> Result<Self, ParseError> {
+        let grid = Grid::chars(rows.lines())?;
+        let cells: Vec<(Point, char)> = grid
+            .iter()
+            .filter(|&(_, &letter)| letter != wildcard)
+            .map(|(point, &letter)| (point, letter))
+            .collect();
+
+        if cells.is_empty() {
+            return Err(ParseError::new(rows, "a template with at least one letter"));
+        }
+        Ok(Self::new(cells))
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// 90 degrees clockwise.
+    pub fn rotate(&self) -> Self {
+        self.transform(|offset| Point::new(offset.col, -offset.row))
+    }
+
+    /// Mirrored left to right.
+    pub fn reflect(&self) -> Self {
+        self.transform(|offset| Point::new(offset.row, -offset.col))
+    }
+
+    fn transform(&self, f: impl Fn(Point) -> Point) -> Self {
+        Self::new(
+            self.cells
+                .iter()
+                .map(|&(offset, letter)| (f(offset), letter))
+                .collect(),
+        )
+    }
+
+    /// The letters and where they are, regardless of order, to tell orientations that look the same apart.
+    fn shape(&self) -> Vec<(Point, char)> {
+        let mut cells = self.cells.clone();
+        cells.sort();
+        let corner = cells.first().map_or(Point::ORIGIN, |&(offset, _)| offset);
+
+        cells
+            .into_iter()
+            .map(|(offset, letter)| (offset - corner, letter))
+            .collect()
+    }
+
+    /// Every distinct orientation of this pattern that `symmetry` allows, this one first.
+    pub fn orientations(&self, symmetry: Symmetry) -> Vec<Pattern> {
+        let mut candidates = vec![self.clone()];
+        if symmetry != Symmetry::Fixed {
+            for _ in 0..3 {
+                let turned = candidates[candidates.len() - 1].rotate();
+                candidates.push(turned);
+            }
+        }
+        if symmetry == Symmetry::RotationsAndReflections {
+            let mirrored: Vec<Pattern> = candidates.iter().map(Pattern::reflect).collect();
+            candidates.extend(mirrored);
+        }
+
+        let mut seen = HashSet::new();
+        candidates.retain(|pattern| seen.insert(pattern.shape()));
+        candidates
+    }
+
+    /// Where the pattern's letters are when its anchor is at `anchor`, if they're all there.
+    fn match_at(&self, grid: &Grid<char>, anchor: Point) -> Option<Vec<Point>> {
+        self.cells
+            .iter()
+            .map(|&(offset, letter)| {
+                let point = anchor + offset;
+                (grid.get(point) == Some(&letter)).then_some(point)
+            })
+            .collect()
+    }
+}
+
+/// What to look for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Query {
+    /// A word in any of the 8 directions.
+    Word(String),
+    /// A 2D template, one row per line, in the orientations `symmetry` allows.
+    Template {
+        rows: String,
+        wildcard: char,
+        symmetry: Symmetry,
+    },
+}
+
+impl Query {
+    /// All the patterns to look for, without duplicates (so a palindrome isn't found twice in the same place).
+    pub fn patterns(&self) -> Result<Vec<Pattern>, ParseError> {
+        let mut patterns = match self {
+            Query::Word(word) => Direction::ALL
+                .iter()
+                .map(|&direction| Pattern::word(word, direction))
+                .collect(),
+            Query::Template {
+                rows,
+                wildcard,
+                symmetry,
+            } => Pattern::template(rows, *wildcard)?.orientations(*symmetry),
+        };
+
+        let mut seen = HashSet::new();
+        patterns.retain(|pattern| !pattern.is_empty() && seen.insert(pattern.shape()));
+        Ok(patterns)
+    }
+}
+
+/// One place a pattern was found.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Match {
+    /// Which of the patterns it was.
+    pub pattern: usize,
+    /// Where each of its letters is, in the pattern's order (so a word's first letter first).
+    pub cells: Vec<Point>,
+}
+
+/// Every place any of `patterns` occurs in `grid`, pattern by pattern, in reading order of their anchors.
+pub fn find_all(grid: &Grid<char>, patterns: &[Pattern]) -> Vec<Match> {
+    let mut matches = vec![];
+    for (idx, pattern) in patterns.iter().enumerate() {
+        let Some(&(_, first)) = pattern.cells.first() else {
+            continue;
+        };
+
+        for anchor in grid.find_all(&first) {
+            if let Some(cells) = pattern.match_at(grid, anchor) {
+                matches.push(Match {
+                    pattern: idx,
+                    cells,
+                });
+            }
+        }
+    }
+
+    matches
+}
+
+// -- tests --
+
+#[test]
+fn test_orientations() {
+    let x_mas = Pattern::template("M.S\n.A.\nM.S", '.').expect("Has letters");
+    assert_eq!(x_mas.len(), 5);
+    assert_eq!(x_mas.orientations(Symmetry::Fixed).len(), 1);
+    assert_eq!(x_mas.orientations(Symmetry::Rotations).len(), 4);
+    // mirroring it is the same as turning it twice:
+    assert_eq!(
+        x_mas.orientations(Symmetry::RotationsAndReflections).len(),
+        4
+    );
+
+    let ell = Pattern::template("A.\nBC", '.').expect("Has letters");
+    assert_eq!(ell.orientations(Symmetry::RotationsAndReflections).len(), 8);
+
+    // turning a word turns its direction:
+    assert_eq!(
+        Pattern::word("XMAS", Direction::Right).rotate(),
+        Pattern::word("XMAS", Direction::Down)
+    );
+    assert_eq!(
+        Pattern::word("XMAS", Direction::UpRight).reflect(),
+        Pattern::word("XMAS", Direction::UpLeft)
+    );
+
+    assert!(Pattern::template("..\n..", '.').is_err());
+}
+
+#[test]
+fn test_find_all() {
+    let grid = Grid::chars(["XMASAMX", ".M.S...", "A.A.A..", "MAS.S.."]).expect("Rectangular");
+
+    let xmas = Query::Word("XMAS".to_string()).patterns().expect("A word");
+    let found = find_all(&grid, &xmas);
+    let starts: Vec<(Point, Point)> = found
+        .iter()
+        .map(|found| (found.cells[0], found.cells[3]))
+        .collect();
+    assert_eq!(
+        starts,
+        vec![
+            (Point::new(0, 0), Point::new(0, 3)),
+            (Point::new(0, 6), Point::new(0, 3)),
+        ]
+    );
+
+    // a palindrome is only found once per place:
+    let level = Query::Word("ASA".to_string()).patterns().expect("A word");
+    assert_eq!(level.len(), 4);
+    let row = Grid::chars(["ASA"]).expect("Rectangular");
+    assert_eq!(find_all(&row, &level).len(), 1);
+
+    let grid = Grid::chars([".M.", "MAS", ".S."]).expect("Rectangular");
+    let plus = Query::Template {
+        rows: ".M.\n.A.\n.S.".to_string(),
+        wildcard: '.',
+        symmetry: Symmetry::Rotations,
+    };
+    let found = find_all(&grid, &plus.patterns().expect("A template"));
+    assert_eq!(found.len(), 2);
+    assert_eq!(
+        found[0].cells,
+        vec![Point::new(0, 1), Point::new(1, 1), Point::new(2, 1)]
+    );
+}
@@ -0,0 +1,132 @@
+use crate::{Graph, Nodes};
+use anyhow::Context;
+use aoc_common::AocResult;
+use std::collections::{BTreeSet, HashSet};
+use std::fmt::Write;
+use std::path::Path;
+
+/// What to draw of the rules, as a Graphviz DOT graph (render it with e.g. `dot -Tsvg rules.dot -o rules.svg`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Dot<'a> {
+    /// Highlight the rules this update breaks.
+    pub update: Option<&'a [i64]>,
+    /// Only draw the pages of `update` and the rules between them, instead of every rule.
+    pub only_update: bool,
+}
+
+impl Dot<'_> {
+    pub fn render(&self, nodes: &Nodes) -> String {
+        let update = self.update.unwrap_or_default();
+        let pages: HashSet<i64> = update.iter().copied().collect();
+        let shown = |page: &i64| !self.only_update || pages.contains(page);
+
+        let broken: HashSet<(i64, i64)> = Graph::new(nodes)
+            .broken(update)
+            .into_iter()
+            .map(|broken| (update[broken.before], update[broken.after]))
+            .collect();
+
+        // sorted, so the same rules always give the same file:
+        let edges: BTreeSet<(i64, i64)> = nodes
+            .iter()
+            .filter(|(before, _)| shown(before))
+            .flat_map(|(&before, after)| after.iter().map(move |&after| (before, after)))
+            .filter(|(_, after)| shown(after))
+            .collect();
+        let mut drawn: BTreeSet<i64> = edges.iter().flat_map(|&(x, y)| [x, y]).collect();
+        drawn.extend(update);
+
+        let mut dot = String::from("digraph rules {\n    rankdir=LR;\n    node [shape=circle];\n");
+        for page in &drawn {
+            let style = if pages.contains(page) {
+                " [style=filled, fillcolor=lightblue]"
+            } else {
+                ""
+            };
+            let _ = writeln!(dot, "    {page}{style};");
+        }
+        for (before, after) in &edges {
+            let style = if broken.contains(&(*before, *after)) {
+                " [color=red, penwidth=2]"
+            } else {
+                ""
+            };
+            let _ = writeln!(dot, "    {before} -> {after}{style};");
+        }
+        dot.push_str("}\n");
+
+        dot
+    }
+
+    pub fn save(&self, nodes: &Nodes, path: &Path) -> AocResult<()> {
+        std::fs::write(path, self.render(nodes))
+            .with_context(|| format!("Can't write {}", path.display()))
+    }
+}
+
+// -- tests --
+
+#[test]
+fn test_render() {
+    let rules: Nodes = [(97, vec![75, 13]), (75, vec![13]), (29, vec![13])]
+        .into_iter()
+        .map(|(page, after)| (page, after.into_iter().collect()))
+        .collect();
+
+    assert_eq!(
+        Dot::default().render(&rules),
+        "\
+digraph rules {
+    rankdir=LR;
+    node [shape=circle];
+    13;
+    29;
+    75;
+    97;
+    29 -> 13;
+    75 -> 13;
+    97 -> 13;
+    97 -> 75;
+}
+"
+    );
+
+    let update = [13, 97, 75];
+    let dot = Dot {
+        update: Some(&update),
+        only_update: true,
+    };
+    assert_eq!(
+        dot.render(&rules),
+        "\
+digraph rules {
+    rankdir=LR;
+    node [shape=circle];
+    13 [style=filled, fillcolor=lightblue];
+    75 [style=filled, fillcolor=lightblue];
+    97 [style=filled, fillcolor=lightblue];
+    75 -> 13 [color=red, penwidth=2];
+    97 -> 13 [color=red, penwidth=2];
+    97 -> 75;
+}
+"
+    );
+}
+
+#[test]
+fn test_save() {
+    let rules: Nodes = [(47, [53].into_iter().collect())].into_iter().collect();
+    let path = std::env::temp_dir().join(format!("day_5_rules_{}.dot", std::process::id()));
+
+    let dot = Dot::default();
+    dot.save(&rules, &path).expect("Writable");
+    assert_eq!(
+        std::fs::read_to_string(&path).expect("Readable"),
+        dot.render(&rules)
+    );
+    let _ = std::fs::remove_file(&path);
+
+    let missing = std::env::temp_dir().join("no_such_dir").join("rules.dot");
+    let err = dot.save(&rules, &missing).expect_err("No directory");
+    assert!(err.to_string().starts_with("Can't write"), "{err}");
+}
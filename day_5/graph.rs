@@ -0,0 +1,317 @@
+use crate::Nodes;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::error::Error;
+use std::fmt::{self, Display};
+
+/// Pages that (transitively) have to come before themselves, in rule order: each one before the next,
+/// and the last one before the first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle {
+    pub pages: Vec<i64>,
+}
+
+impl Display for Cycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the rules go round in a cycle: ")?;
+        for page in &self.pages {
+            write!(f, "{page} -> ")?;
+        }
+        write!(f, "{}", self.pages.first().copied().unwrap_or_default())
+    }
+}
+
+impl Error for Cycle {}
+
+/// A rule `X|Y` that an update breaks, by where its pages are in that update (Y comes before X).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Broken {
+    pub before: usize,
+    pub after: usize,
+}
+
+/// The rules of a subset of pages, by their positions in that subset.
+struct Subgraph {
+    successors: Vec<Vec<usize>>,
+    predecessors: Vec<Vec<usize>>,
+}
+
+/// The ordering rules as a dependency graph, with an edge X -> Y for every rule `X|Y`.
+/// The full set of rules doesn't have to be acyclic, only the rules between the pages of one update.
+pub struct Graph<'a> {
+    rules: &'a Nodes,
+}
+
+impl<'a> Graph<'a> {
+    pub fn new(rules: &'a Nodes) -> Self {
+        Self { rules }
+    }
+
+    /// Where every page is in `pages` (the first time, if it's there more than once).
+    fn positions(pages: &[i64]) -> HashMap<i64, usize> {
+        let mut positions = HashMap::with_capacity(pages.len());
+        for (idx, &page) in pages.iter().enumerate() {
+            positions.entry(page).or_insert(idx);
+        }
+        positions
+    }
+
+    /// Every rule between two of `pages`, as (X, Y) positions, in O(n + rules).
+    fn edges(&self, pages: &[i64]) -> Vec<(usize, usize)> {
+        let positions = Self::positions(pages);
+        let mut edges = vec![];
+        for (before, page) in pages.iter().enumerate() {
+            for after in self.rules.get(page).into_iter().flatten() {
+                if let Some(&after) = positions.get(after) {
+                    edges.push((before, after));
+                }
+            }
+        }
+
+        edges
+    }
+
+    fn subgraph(&self, pages: &[i64]) -> Subgraph {
+        let mut successors = vec![vec![]; pages.len()];
+        let mut predecessors = vec![vec![]; pages.len()];
+        for (before, after) in self.edges(pages) {
+            successors[before].push(after);
+            predecessors[after].push(before);
+        }
+
+        Subgraph {
+            successors,
+            predecessors,
+        }
+    }
+
+    /// The rules `update` breaks, in the order of their X and then Y pages.
+    pub fn broken(&self, update: &[i64]) -> Vec<Broken> {
+        let mut broken: Vec<Broken> = self
+            .edges(update)
+            .into_iter()
+            .filter(|&(before, after)| after < before)
+            .map(|(before, after)| Broken { before, after })
+            .collect();
+        broken.sort();
+        broken
+    }
+
+    /// Whether `update` follows every rule between its pages.
+    pub fn is_ordered(&self, update: &[i64]) -> bool {
+        self.edges(update)
+            .into_iter()
+            .all(|(before, after)| before < after)
+    }
+
+    /// `pages` in an order that follows every rule between them (Kahn's algorithm).
+    /// When several pages could go next, the one that came first in `pages` does, so pages without
+    /// rules between them stay in their original order and an ordered update comes back unchanged.
+    pub fn sort(&self, pages: &[i64]) -> Result<Vec<i64>, Cycle> {
+        self.sort_keeping(pages, &[])
+    }
+
+    /// Like `sort`, but the pages at the positions in `kept` (in increasing order) also stay in the order
+    /// they're in, as if there were rules between them. They have to follow the rules already, like the
+    /// ones `largest_ordered` picks, or the extra rules can make a cycle.
+    pub fn sort_keeping(&self, pages: &[i64], kept: &[usize]) -> Result<Vec<i64>, Cycle> {
+        let mut graph = self.subgraph(pages);
+        for pair in kept.windows(2) {
+            graph.successors[pair[0]].push(pair[1]);
+            graph.predecessors[pair[1]].push(pair[0]);
+        }
+        let mut waiting_for: Vec<usize> = graph.predecessors.iter().map(Vec::len).collect();
+
+        let mut ready: BinaryHeap<Reverse<usize>> = (0..pages.len())
+            .filter(|&idx| waiting_for[idx] == 0)
+            .map(Reverse)
+            .collect();
+
+        let mut sorted = Vec::with_capacity(pages.len());
+        while let Some(Reverse(idx)) = ready.pop() {
+            sorted.push(pages[idx]);
+            for &next in &graph.successors[idx] {
+                waiting_for[next] -= 1;
+                if waiting_for[next] == 0 {
+                    ready.push(Reverse(next));
+                }
+            }
+        }
+
+        if sorted.len() < pages.len() {
+            return Err(Self::cycle(pages, &graph, &waiting_for));
+        }
+        Ok(sorted)
+    }
+
+    /// For every position in `pages`, the positions the rules put after it, directly or through other pages.
+    fn after(&self, pages: &[i64]) -> Vec<Vec<bool>> {
+        let graph = self.subgraph(pages);
+        let mut after = vec![vec![false; pages.len()]; pages.len()];
+        for (start, reached) in after.iter_mut().enumerate() {
+            let mut stack = vec![start];
+            while let Some(idx) = stack.pop() {
+                for &next in &graph.successors[idx] {
+                    if !reached[next] {
+                        reached[next] = true;
+                        stack.push(next);
+                    }
+                }
+            }
+        }
+
+        after
+    }
+
+    /// The positions of the largest set of pages in `pages` that already follow the rules between them,
+    /// including the ones that only follow through other pages. Those can stay where they are, and moving
+    /// every other page once is the fewest moves that fix the update.
+    ///
+    /// Two pages are in each other's way when the later one has to come first. That's a partial order,
+    /// so the largest set without any two in each other's way is its largest antichain, which comes from
+    /// a maximum matching (Dilworth's and König's theorems). Only meaningful when the rules between
+    /// `pages` don't go round in a cycle.
+    pub fn largest_ordered(&self, pages: &[i64]) -> Vec<usize> {
+        let after = self.after(pages);
+        let len = pages.len();
+        // blocked_by[idx]: the later positions that have to come before it:
+        let blocked_by: Vec<Vec<usize>> = (0..len)
+            .map(|idx| (idx + 1..len).filter(|&later| after[later][idx]).collect())
+            .collect();
+
+        // the maximum matching, with Kuhn's augmenting paths; matched[later] = idx:
+        fn augment(
+            idx: usize,
+            blocked_by: &[Vec<usize>],
+            matched: &mut [Option<usize>],
+            tried: &mut [bool],
+        ) -> bool {
+            for &later in &blocked_by[idx] {
+                if !tried[later] {
+                    tried[later] = true;
+                    let free = match matched[later] {
+                        None => true,
+                        Some(other) => augment(other, blocked_by, matched, tried),
+                    };
+                    if free {
+                        matched[later] = Some(idx);
+                        return true;
+                    }
+                }
+            }
+            false
+        }
+
+        let mut matched = vec![None; len];
+        for idx in 0..len {
+            augment(idx, &blocked_by, &mut matched, &mut vec![false; len]);
+        }
+
+        // König: walk alternating paths from the unmatched pages on the left, out along any edge and back
+        // along matched ones. The antichain is what's reached on the left, but not on the right.
+        let mut has_match = vec![false; len];
+        for &idx in matched.iter().flatten() {
+            has_match[idx] = true;
+        }
+        let mut left: Vec<bool> = has_match.iter().map(|&has| !has).collect();
+        let mut right = vec![false; len];
+        let mut stack: Vec<usize> = (0..len).filter(|&idx| left[idx]).collect();
+        while let Some(idx) = stack.pop() {
+            for &later in &blocked_by[idx] {
+                if !right[later] {
+                    right[later] = true;
+                    if let Some(back) = matched[later].filter(|&back| !left[back]) {
+                        left[back] = true;
+                        stack.push(back);
+                    }
+                }
+            }
+        }
+
+        (0..len).filter(|&idx| left[idx] && !right[idx]).collect()
+    }
+
+    /// A cycle among the pages Kahn's algorithm couldn't place. Each of them still waits for another one of
+    /// them, so following those backwards has to come round to a page it's seen before.
+    fn cycle(pages: &[i64], graph: &Subgraph, waiting_for: &[usize]) -> Cycle {
+        let stuck = |idx: &usize| waiting_for[*idx] > 0;
+        let start = (0..pages.len())
+            .find(stuck)
+            .expect("Some page has to be stuck");
+
+        let mut seen = vec![None; pages.len()];
+        let mut path = vec![];
+        let mut at = start;
+        while seen[at].is_none() {
+            seen[at] = Some(path.len());
+            path.push(at);
+            at = *graph.predecessors[at]
+                .iter()
+                .find(|idx| stuck(idx))
+                .expect("A stuck page waits for another stuck page");
+        }
+
+        // the path walked backwards, so the cycle is in reverse rule order:
+        let mut cycle: Vec<usize> = path[seen[at].unwrap_or_default()..].to_vec();
+        cycle.reverse();
+
+        // start at whichever page comes first in the update:
+        let first = (0..cycle.len()).min_by_key(|&idx| cycle[idx]).unwrap_or(0);
+        cycle.rotate_left(first);
+
+        Cycle {
+            pages: cycle.into_iter().map(|idx| pages[idx]).collect(),
+        }
+    }
+}
+
+// -- tests --
+
+#[test]
+fn test_sort() {
+    let rules: Nodes = [
+        (47, vec![53, 13, 61, 29]),
+        (97, vec![13, 61, 47]),
+        (75, vec![29]),
+    ]
+    .into_iter()
+    .map(|(page, after)| (page, after.into_iter().collect()))
+    .collect();
+    let graph = Graph::new(&rules);
+
+    assert!(graph.is_ordered(&[97, 47, 61, 53]));
+    assert_eq!(graph.sort(&[97, 47, 61, 53]), Ok(vec![97, 47, 61, 53]));
+
+    assert!(!graph.is_ordered(&[61, 13, 29, 75, 47]));
+    let broken: Vec<(usize, usize)> = graph
+        .broken(&[61, 13, 29, 75, 47])
+        .iter()
+        .map(|broken| (broken.before, broken.after))
+        .collect();
+    assert_eq!(broken, vec![(3, 2), (4, 0), (4, 1), (4, 2)]);
+    // pages without rules between them keep their order:
+    assert_eq!(
+        graph.sort(&[61, 13, 29, 75, 47]),
+        Ok(vec![75, 47, 61, 13, 29])
+    );
+    assert_eq!(graph.sort(&[5, 4, 3]), Ok(vec![5, 4, 3]));
+}
+
+#[test]
+fn test_cycle() {
+    let rules: Nodes = [(1, vec![2]), (2, vec![3]), (3, vec![1]), (4, vec![1])]
+        .into_iter()
+        .map(|(page, after)| (page, after.into_iter().collect()))
+        .collect();
+    let graph = Graph::new(&rules);
+
+    let cycle = graph.sort(&[4, 3, 2, 1]).expect_err("Circular");
+    assert_eq!(cycle.pages, vec![3, 1, 2]);
+    assert_eq!(
+        cycle.to_string(),
+        "the rules go round in a cycle: 3 -> 1 -> 2 -> 3"
+    );
+
+    // without page 3 the rest are fine:
+    assert_eq!(graph.sort(&[2, 1, 4]), Ok(vec![4, 1, 2]));
+}
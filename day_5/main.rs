@@ -1,210 +1,225 @@
 #![allow(dead_code)]
 
+use aoc_common::{parse_in, Answer, AocResult, Explain, Format, ParseError, PuzzleInput, Solution};
 use std::collections::{HashMap, HashSet};
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
 
-type Nodes = HashMap<i64, HashSet<i64>>;
+mod dot;
+mod graph;
+mod report;
 
-fn task_valid(task: &Vec<i64>, nodes: &Nodes) -> bool {
-    let empty: HashSet<i64> = HashSet::new();
+pub use dot::Dot;
+pub use graph::{Broken, Cycle, Graph};
+pub use report::{BrokenRule, Move, UpdateReport};
 
-    let mut nodes_inv: Nodes = Nodes::new();
+/// For every page, the pages that have to come after it.
+pub type Nodes = HashMap<i64, HashSet<i64>>;
+type Pages = (Vec<Vec<i64>>, Nodes);
 
-    // build inverse relationships:
-    for step in task {
-        for dep in nodes.get(step).unwrap_or(&empty) {
-            nodes_inv.entry(*dep).or_default().insert(*step);
-        }
-    }
-
-    let mut seen: HashSet<i64> = HashSet::new();
-
-    for step in task {
-        let depends = nodes_inv.get(step).unwrap_or(&empty);
+fn middle(task: &[i64]) -> i64 {
+    task[task.len() / 2]
+}
 
-        if depends != &seen {
-            // error in dependency tree
-            return false;
-        }
-        seen.insert(*step);
-    }
+fn parse_rule(line: &str) -> Result<(i64, i64), ParseError> {
+    let (left, right) = line
+        .split_once("|")
+        .ok_or_else(|| ParseError::in_line(line, line, "a rule like `47|53`"))?;
 
-    true
+    Ok((
+        parse_in(line, left, "a page number")?,
+        parse_in(line, right, "a page number")?,
+    ))
 }
 
-fn try_task(task: &Vec<i64>, nodes: &Nodes) -> i64 {
-    if task_valid(task, nodes) {
-        // middle value
-        task[task.len() / 2]
-    } else {
-        0
-    }
+fn parse_task(line: &str) -> Result<Vec<i64>, ParseError> {
+    line.split(",")
+        .map(|it| parse_in(line, it, "a page number"))
+        .collect()
 }
 
-fn parse_pages(file: FileHandle) -> (Vec<Vec<i64>>, Nodes) {
+fn parse_pages(input: &PuzzleInput) -> AocResult<Pages> {
     let empty_string: &'static str = "";
     let mut nodes: Nodes = HashMap::new();
     let mut tasks = vec![];
     let mut first_half = true;
 
-    for line in file.map_while(Result::ok) {
+    for (idx, line) in input.lines().enumerate() {
         if line == empty_string {
             first_half = false;
         } else if first_half {
-            let mut parts = line.split("|");
-            let left = parts
-                .next()
-                .expect("Should be 2")
-                .parse::<i64>()
-                .expect("Should be a number");
-
-            let right = parts
-                .next()
-                .expect("Should be 2")
-                .parse::<i64>()
-                .expect("Should be a number");
+            let (left, right) = parse_rule(line).map_err(|err| err.on_line(idx + 1))?;
 
             nodes.entry(left).or_default().insert(right);
         } else {
-            let parts = line.split(",");
-            let task: Vec<i64> = parts
-                .map(|it| it.parse().expect("Should be number"))
-                .collect();
+            let task = parse_task(line).map_err(|err| err.on_line(idx + 1))?;
             tasks.push(task)
         }
     }
 
-    (tasks, nodes)
+    Ok((tasks, nodes))
 }
 
-async fn simple(file: FileHandle) -> anyhow::Result<i64> {
-    let (tasks, nodes) = parse_pages(file);
-
-    let mut result = 0;
+fn simple((tasks, nodes): &Pages) -> AocResult {
+    let graph = Graph::new(nodes);
 
-    for task in tasks {
-        result += try_task(&task, &nodes);
-    }
-
-    Ok(result)
+    Ok(tasks
+        .iter()
+        .filter(|task| graph.is_ordered(task))
+        .map(|task| middle(task))
+        .sum())
 }
 
-fn rebuild_nodes_relevant_inv(task: &Vec<i64>, nodes: &Nodes) -> Nodes {
-    let mut nodes_relevant_inv: Nodes = Nodes::new();
-    let empty = HashSet::new();
+fn advanced((tasks, nodes): &Pages) -> AocResult {
+    let graph = Graph::new(nodes);
 
-    // build inverse relationships:
-    for step in task {
-        for dep in nodes.get(step).unwrap_or(&empty) {
-            if task.contains(dep) {
-                nodes_relevant_inv.entry(*dep).or_default().insert(*step);
-            }
+    let mut result = 0;
+    for task in tasks {
+        if !graph.is_ordered(task) {
+            result += middle(&graph.sort(task)?);
         }
     }
 
-    nodes_relevant_inv
+    Ok(result)
 }
 
-fn fix_task(task: &[i64], nodes: &Nodes) -> i64 {
-    let mut todo = task.to_owned();
+pub struct Day5;
 
-    let max_loop = 1000;
-    let mut curr_loop = 0;
+impl Solution for Day5 {
+    const DAY: u8 = 5;
 
-    let mut result: Vec<i64> = vec![];
+    type Input = Pages;
+    type Config = ();
 
-    loop {
-        if curr_loop > max_loop {
-            panic!("Looped too much!!!")
-        }
-        // rip perf but ok:
-        let nodes_relevant_inv = rebuild_nodes_relevant_inv(&todo, nodes);
-
-        for (idx, item) in todo.iter().enumerate() {
-            if !nodes_relevant_inv.contains_key(item) {
-                // no dependency yippie
-                result.push(*item);
-                todo.remove(idx);
-
-                break;
-            }
-        }
+    fn parse(input: &PuzzleInput) -> AocResult<Self::Input> {
+        parse_pages(input)
+    }
 
-        if todo.is_empty() {
-            break;
-        }
+    fn part1(input: &Self::Input, _config: &()) -> AocResult<Answer> {
+        Ok(simple(input)?.into())
+    }
 
-        curr_loop += 1;
+    fn part2(input: &Self::Input, _config: &()) -> AocResult<Answer> {
+        Ok(advanced(input)?.into())
     }
 
-    result[result.len() / 2]
+    /// Every update (or just the one in focus), with the rules it breaks and how to fix it;
+    /// or the rules as a Graphviz graph, with the ones the update in focus breaks in red.
+    fn explain((tasks, nodes): &Self::Input, _config: &(), options: &Explain) -> AocResult<String> {
+        let focus = options
+            .focus
+            .map(|update| match update.checked_sub(1) {
+                Some(idx) if idx < tasks.len() => Ok(idx),
+                _ => Err(anyhow::anyhow!(
+                    "There's no update {update}, only 1 to {}",
+                    tasks.len()
+                )),
+            })
+            .transpose()?;
+
+        let reports = || -> Vec<UpdateReport> {
+            tasks
+                .iter()
+                .enumerate()
+                .filter(|(idx, _)| focus.is_none_or(|focus| focus == *idx))
+                .map(|(idx, task)| UpdateReport::new(idx + 1, task, nodes))
+                .collect()
+        };
+
+        Ok(match options.format {
+            Format::Text => reports().iter().map(UpdateReport::to_string).collect(),
+            Format::Json => serde_json::to_string_pretty(&reports())? + "\n",
+            Format::Dot => Dot {
+                update: focus.map(|idx| tasks[idx].as_slice()),
+                only_update: options.only_focus,
+            }
+            .render(nodes),
+        })
+    }
 }
 
-async fn advanced(file: FileHandle) -> anyhow::Result<i64> {
-    let (tasks, nodes) = parse_pages(file);
+// -- tests --
 
-    let mut result = 0;
+#[test]
+fn test_simple_minimal() {
+    let answer = 143;
 
-    for task in tasks {
-        if !task_valid(&task, &nodes) {
-            result += fix_task(&task, &nodes);
-        }
-    }
+    let file = aoc_common::input_or_skip!(Day5, "example");
 
-    Ok(result)
+    assert_eq!(Day5::solve(1, &file).expect("Oof 1"), answer);
 }
 
-// -- tests --
+#[test]
+fn test_simple() {
+    let answer = 7365;
 
-type FileHandle = io::Lines<io::BufReader<File>>;
+    let file = aoc_common::input_or_skip!(Day5, "input");
 
-async fn read_lines<P: AsRef<Path>>(filename: P) -> io::Result<FileHandle> {
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
+    assert_eq!(Day5::solve(1, &file).expect("Oof 1"), answer);
 }
 
-#[tokio::test]
-async fn test_simple_minimal() {
-    let answer = 143;
+#[test]
+fn test_advanced_minimal() {
+    let answer = 123;
 
-    let file = read_lines("minimal.txt")
-        .await
-        .expect("Should be able to read minimal.txt");
+    let file = aoc_common::input_or_skip!(Day5, "example");
 
-    assert_eq!(simple(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day5::solve(2, &file).expect("Oof 1"), answer);
 }
 
-#[tokio::test]
-async fn test_simple() {
-    let answer = 7365;
+#[test]
+fn test_advanced() {
+    let answer = 5770;
 
-    let file = read_lines("simple.txt")
-        .await
-        .expect("Should be able to read simple.txt");
+    let file = aoc_common::input_or_skip!(Day5, "input");
 
-    assert_eq!(simple(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day5::solve(2, &file).expect("Oof 2"), answer);
 }
 
-#[tokio::test]
-async fn test_advanced_minimal() {
-    let answer = 123;
-
-    let file = read_lines("minimal.txt")
-        .await
-        .expect("Should be able to read minimal.txt");
+#[test]
+fn test_parse_error() {
+    let err = parse_rule("47|x3").expect_err("Should not parse");
 
-    assert_eq!(advanced(file).await.expect("Oof 1"), answer);
+    assert_eq!(err.column, 4);
+    assert_eq!(err.text, "x3");
 }
 
-#[tokio::test]
-async fn test_advanced() {
-    let answer = 5770;
+#[test]
+fn test_cyclic_rules() {
+    let input = PuzzleInput::from("1|2\n2|3\n3|1\n\n1,2,3\n3,2\n");
 
-    let file = read_lines("simple.txt")
-        .await
-        .expect("Should be able to read advanced.txt");
+    assert_eq!(Day5::solve(1, &input).expect("Oof 1"), 0);
+    let err = Day5::solve(2, &input).expect_err("Can't be sorted");
+    assert_eq!(
+        err.to_string(),
+        "the rules go round in a cycle: 1 -> 2 -> 3 -> 1"
+    );
+}
 
-    assert_eq!(advanced(file).await.expect("Oof 2"), answer);
+#[test]
+fn test_explain_focus() {
+    let input = PuzzleInput::from("97|75\n75|13\n\n97,75,13\n13,97,75\n");
+    let pages = Day5::parse(&input).expect("Parsable");
+
+    let focus = |format: Format, focus: usize, only_focus: bool| {
+        let options = Explain {
+            format,
+            focus: Some(focus),
+            only_focus,
+            ..Default::default()
+        };
+        Day5::explain(&pages, &(), &options)
+    };
+
+    let text = focus(Format::Text, 2, false).expect("Explainable");
+    assert!(text.starts_with("update 2 (13,97,75): invalid\n"), "{text}");
+    assert!(!text.contains("update 1"));
+
+    let dot = focus(Format::Dot, 2, true).expect("Explainable");
+    assert!(
+        dot.contains("    75 -> 13 [color=red, penwidth=2];\n"),
+        "{dot}"
+    );
+    assert!(dot.contains("    97 -> 75;\n"), "{dot}");
+
+    let err = focus(Format::Text, 3, false).expect_err("Only two updates");
+    assert_eq!(err.to_string(), "There's no update 3, only 1 to 2");
 }
@@ -0,0 +1,315 @@
+use crate::{Cycle, Graph, Nodes};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+
+/// A rule `x|y` an update breaks by having `y` first, with both pages' positions in the update.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BrokenRule {
+    pub x: i64,
+    pub y: i64,
+    pub x_position: usize,
+    pub y_position: usize,
+}
+
+/// One page that has to move to fix the update, and where it ends up.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Move {
+    pub page: i64,
+    pub from: usize,
+    pub to: usize,
+}
+
+/// Whether an update follows the rules, and if not, which ones it breaks and how to fix it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UpdateReport {
+    /// 1-based, among the updates.
+    pub update: usize,
+    pub pages: Vec<i64>,
+    pub valid: bool,
+    pub broken: Vec<BrokenRule>,
+    /// The order part 2 puts it in, unless its rules go round in a cycle.
+    pub reordered: Option<Vec<i64>>,
+    /// The fewest pages to move to fix the update; the rest already follow the rules between them, and stay
+    /// in the order they're in.
+    pub moves: Vec<Move>,
+    /// The update after `moves`: it follows the rules too, but isn't always `reordered`.
+    pub moved: Option<Vec<i64>>,
+    /// The pages whose rules go round, if they do.
+    pub cycle: Option<Vec<i64>>,
+}
+
+impl UpdateReport {
+    pub fn new(update: usize, pages: &[i64], nodes: &Nodes) -> Self {
+        let graph = Graph::new(nodes);
+        let broken: Vec<BrokenRule> = graph
+            .broken(pages)
+            .into_iter()
+            .map(|broken| BrokenRule {
+                x: pages[broken.before],
+                y: pages[broken.after],
+                x_position: broken.before,
+                y_position: broken.after,
+            })
+            .collect();
+
+        let (reordered, cycle) = match graph.sort(pages) {
+            Ok(sorted) => (Some(sorted), None),
+            Err(cycle) => (None, Some(cycle.pages)),
+        };
+        let (moved, moves) = match fewest_moves(&graph, pages) {
+            Ok((moved, moves)) => (Some(moved), moves),
+            Err(_) => (None, vec![]),
+        };
+
+        Self {
+            update,
+            pages: pages.to_vec(),
+            valid: broken.is_empty(),
+            broken,
+            reordered,
+            moves,
+            moved,
+            cycle,
+        }
+    }
+}
+
+/// The fewest pages to move to fix `pages`: every page but the largest set that already follows the rules,
+/// and the order that leaves the update in.
+fn fewest_moves(graph: &Graph, pages: &[i64]) -> Result<(Vec<i64>, Vec<Move>), Cycle> {
+    let kept = graph.largest_ordered(pages);
+    let moved = graph.sort_keeping(pages, &kept)?;
+
+    let positions: HashMap<i64, usize> = moved
+        .iter()
+        .enumerate()
+        .map(|(idx, &page)| (page, idx))
+        .collect();
+    let mut stays = vec![false; pages.len()];
+    for idx in kept {
+        stays[idx] = true;
+    }
+
+    let moves = (0..pages.len())
+        .filter(|&idx| !stays[idx])
+        .map(|idx| Move {
+            page: pages[idx],
+            from: idx,
+            to: positions[&pages[idx]],
+        })
+        .collect();
+
+    Ok((moved, moves))
+}
+
+impl Display for UpdateReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pages = |pages: &[i64]| {
+            let pages: Vec<String> = pages.iter().map(i64::to_string).collect();
+            pages.join(",")
+        };
+
+        if self.valid {
+            return writeln!(f, "update {} ({}): valid", self.update, pages(&self.pages));
+        }
+
+        writeln!(
+            f,
+            "update {} ({}): invalid",
+            self.update,
+            pages(&self.pages)
+        )?;
+        for rule in &self.broken {
+            writeln!(
+                f,
+                "  breaks {}|{}: {} is at position {}, {} at {}",
+                rule.x, rule.y, rule.x, rule.x_position, rule.y, rule.y_position
+            )?;
+        }
+
+        if let Some(cycle) = &self.cycle {
+            return writeln!(f, "  can't be fixed, the rules go round: {}", pages(cycle));
+        }
+        if let Some(reordered) = &self.reordered {
+            writeln!(f, "  fixed: {}", pages(reordered))?;
+        }
+        if let Some(moved) = &self.moved {
+            writeln!(
+                f,
+                "  or with the fewest moves ({}): {}",
+                self.moves.len(),
+                pages(moved)
+            )?;
+        }
+        for step in &self.moves {
+            writeln!(
+                f,
+                "  move {} from position {}, to end up at position {}",
+                step.page, step.from, step.to
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+// -- tests --
+
+#[test]
+fn test_report() {
+    let rules: Nodes = [(97, vec![75, 13]), (75, vec![13, 47]), (47, vec![13])]
+        .into_iter()
+        .map(|(page, after)| (page, after.into_iter().collect()))
+        .collect();
+
+    let valid = UpdateReport::new(1, &[97, 75, 47, 13], &rules);
+    assert!(valid.valid);
+    assert!(valid.moves.is_empty());
+    assert_eq!(valid.to_string(), "update 1 (97,75,47,13): valid\n");
+
+    let report = UpdateReport::new(2, &[97, 13, 75, 47], &rules);
+    assert!(!report.valid);
+    assert_eq!(
+        report.broken,
+        vec![
+            BrokenRule {
+                x: 75,
+                y: 13,
+                x_position: 2,
+                y_position: 1
+            },
+            BrokenRule {
+                x: 47,
+                y: 13,
+                x_position: 3,
+                y_position: 1
+            },
+        ]
+    );
+    assert_eq!(report.reordered, Some(vec![97, 75, 47, 13]));
+    // moving 13 to the end is enough:
+    assert_eq!(
+        report.moves,
+        vec![Move {
+            page: 13,
+            from: 1,
+            to: 3
+        }]
+    );
+    assert_eq!(
+        report.to_string(),
+        "\
+update 2 (97,13,75,47): invalid
+  breaks 75|13: 75 is at position 2, 13 at 1
+  breaks 47|13: 47 is at position 3, 13 at 1
+  fixed: 97,75,47,13
+  or with the fewest moves (1): 97,75,47,13
+  move 13 from position 1, to end up at position 3
+"
+    );
+
+    let reversed = UpdateReport::new(3, &[13, 47, 75, 97], &rules);
+    assert_eq!(reversed.moves.len(), 3);
+}
+
+#[test]
+fn test_fewest_moves() {
+    // every page before every bigger one:
+    let ascending = |pages: &[i64]| -> Nodes {
+        pages
+            .iter()
+            .map(|&page| (page, pages.iter().copied().filter(|&p| p > page).collect()))
+            .collect()
+    };
+    let count = |pages: &[i64]| {
+        let rules = ascending(pages);
+        UpdateReport::new(1, pages, &rules).moves.len()
+    };
+    assert_eq!(count(&[1, 2, 3]), 0);
+    assert_eq!(count(&[3, 1, 2]), 1);
+    assert_eq!(count(&[2, 4, 1, 3, 5]), 2);
+    assert_eq!(count(&[5, 4, 3, 2, 1]), 4);
+
+    // part 2's order is 3,0,2,1, two moves away, but moving 0 to the front is enough:
+    let rules: Nodes = [(0, [1, 2].into_iter().collect())].into_iter().collect();
+    let report = UpdateReport::new(1, &[2, 3, 1, 0], &rules);
+    assert_eq!(report.reordered, Some(vec![3, 0, 2, 1]));
+    assert_eq!(report.moved, Some(vec![0, 2, 3, 1]));
+    assert_eq!(
+        report.moves,
+        vec![Move {
+            page: 0,
+            from: 3,
+            to: 0
+        }]
+    );
+
+    // pages 1 and 3 have no rule between them, but 3|2 and 2|1 still put 3 first, so they can't both stay:
+    let rules: Nodes = [(3, vec![2]), (2, vec![1])]
+        .into_iter()
+        .map(|(page, after)| (page, after.into_iter().collect()))
+        .collect();
+    let report = UpdateReport::new(1, &[1, 2, 3], &rules);
+    assert_eq!(report.moves.len(), 2);
+    assert_eq!(report.moved, Some(vec![3, 2, 1]));
+}
+
+#[test]
+fn test_fewest_moves_brute_force() {
+    use std::collections::{HashSet, VecDeque};
+
+    let mut seed = 5u64;
+    let mut random = |below: u64| {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (seed >> 33) % below
+    };
+
+    for _ in 0..300 {
+        let len = 1 + random(6) as usize;
+        // rules only go from smaller to bigger pages, so they never go round:
+        let mut rules = Nodes::new();
+        for before in 0..len as i64 {
+            for after in before + 1..len as i64 {
+                if random(100) < 30 {
+                    rules.entry(before).or_default().insert(after);
+                }
+            }
+        }
+        let mut pages: Vec<i64> = (0..len as i64).collect();
+        for idx in (1..len).rev() {
+            pages.swap(idx, random(idx as u64 + 1) as usize);
+        }
+
+        // breadth first through every order, a move being taking one page out and putting it back anywhere:
+        let graph = Graph::new(&rules);
+        let mut seen = HashSet::from([pages.clone()]);
+        let mut queue = VecDeque::from([(pages.clone(), 0)]);
+        let fewest = loop {
+            let (order, moves) = queue.pop_front().expect("Some order follows the rules");
+            if graph.is_ordered(&order) {
+                break moves;
+            }
+            for from in 0..len {
+                for to in 0..len {
+                    let mut next = order.clone();
+                    let page = next.remove(from);
+                    next.insert(to, page);
+                    if seen.insert(next.clone()) {
+                        queue.push_back((next, moves + 1));
+                    }
+                }
+            }
+        };
+
+        let report = UpdateReport::new(1, &pages, &rules);
+        assert_eq!(report.moves.len(), fewest, "{pages:?} with {rules:?}");
+        let moved = report.moved.expect("No cycle");
+        assert!(graph.is_ordered(&moved), "{moved:?}");
+        for step in &report.moves {
+            assert_eq!(moved[step.to], step.page);
+        }
+    }
+}
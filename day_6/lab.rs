@@ -0,0 +1,354 @@
+use aoc_common::{Direction, Grid, Point};
+
+/// A jump that runs off the map.
+const EXIT: u32 = u32::MAX;
+
+/// A dense set of cells (or states), one bit each.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bits {
+    words: Vec<u64>,
+}
+
+impl Bits {
+    pub fn new(len: usize) -> Self {
+        Self {
+            words: vec![0; len.div_ceil(64)],
+        }
+    }
+
+    pub fn get(&self, idx: usize) -> bool {
+        self.words[idx / 64] & (1 << (idx % 64)) != 0
+    }
+
+    /// Add `idx`, returning whether it's new.
+    pub fn set(&mut self, idx: usize) -> bool {
+        let word = &mut self.words[idx / 64];
+        let bit = 1 << (idx % 64);
+        let new = *word & bit == 0;
+        *word |= bit;
+        new
+    }
+
+    /// How many are in both sets.
+    pub fn overlap(&self, other: &Bits) -> usize {
+        self.words
+            .iter()
+            .zip(&other.words)
+            .map(|(a, b)| (a & b).count_ones() as usize)
+            .sum()
+    }
+
+    pub fn count(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+}
+
+/// Where a guard starts and which way it's facing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Guard {
+    pub position: Point,
+    pub facing: Direction,
+}
+
+/// The first time a guard walked onto a cell: an obstruction there is the first thing to change its route.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Entry {
+    pub cell: usize,
+    pub from: usize,
+    pub facing: usize,
+}
+
+/// How a guard's walk ends. Steps count every move forward and every turn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// It walked off the map, `steps` steps in (counting the one off the map).
+    Exits { steps: usize },
+    /// It got into a loop: from step `entered_at` on, standing on `entry` facing `facing`,
+    /// it does the same `period` steps over and over.
+    Loops {
+        entry: Point,
+        facing: Direction,
+        entered_at: usize,
+        period: usize,
+    },
+}
+
+/// Where a guard went on the lab as it is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Route {
+    pub visited: Bits,
+    /// In the order the guard got there, without the cell it started on.
+    pub entries: Vec<Entry>,
+    pub outcome: Outcome,
+}
+
+impl Route {
+    pub fn exits(&self) -> bool {
+        matches!(self.outcome, Outcome::Exits { .. })
+    }
+}
+
+/// Remembers the states of one loop check at a time, without clearing anything in between:
+/// a state counts as seen when it's stamped with the current check's number.
+pub struct Seen {
+    stamps: Vec<u32>,
+    stamp: u32,
+}
+
+impl Seen {
+    pub fn new(lab: &Lab) -> Self {
+        Self {
+            stamps: vec![0; lab.cells() * 4],
+            stamp: 0,
+        }
+    }
+
+    fn next_check(&mut self) {
+        self.stamp += 1;
+    }
+
+    /// Mark `state`, returning whether this check had seen it already.
+    fn visit(&mut self, state: usize) -> bool {
+        let seen = self.stamps[state] == self.stamp;
+        self.stamps[state] = self.stamp;
+        seen
+    }
+}
+
+/// The lab as a dense grid of obstacles, with for every cell and direction where a guard walking
+/// that way would stop. Directions are indices into `Direction::CARDINAL`, so turning right is adding one.
+pub struct Lab {
+    width: usize,
+    height: usize,
+    walls: Bits,
+    /// jumps[facing][cell]: the last free cell before the next obstacle, or `EXIT`.
+    jumps: [Vec<u32>; 4],
+}
+
+impl Lab {
+    pub fn new(grid: &Grid<char>) -> Self {
+        let (width, height) = (grid.width(), grid.height());
+        let mut walls = Bits::new(width * height);
+        for (idx, (_, &cell)) in grid.iter().enumerate() {
+            if cell == '#' {
+                walls.set(idx);
+            }
+        }
+
+        let mut lab = Self {
+            width,
+            height,
+            walls,
+            jumps: Default::default(),
+        };
+        lab.jumps = [0, 1, 2, 3].map(|facing| lab.jump_table(facing));
+        lab
+    }
+
+    /// Every cell's jump, filled in so the cell ahead is always done first: going up or left
+    /// that's the cells before it in reading order, going down or right the ones after it.
+    fn jump_table(&self, facing: usize) -> Vec<u32> {
+        let mut jumps = vec![EXIT; self.cells()];
+        let order: Box<dyn Iterator<Item = usize>> = match Direction::CARDINAL[facing] {
+            Direction::Up | Direction::Left => Box::new(0..self.cells()),
+            _ => Box::new((0..self.cells()).rev()),
+        };
+
+        for cell in order {
+            jumps[cell] = match self.step(cell, facing) {
+                None => EXIT,
+                Some(ahead) if self.walls.get(ahead) => cell as u32,
+                Some(ahead) => jumps[ahead],
+            };
+        }
+
+        jumps
+    }
+
+    pub fn cells(&self) -> usize {
+        self.width * self.height
+    }
+
+    pub fn index(&self, point: Point) -> Option<usize> {
+        let in_bounds = (0..self.height as i64).contains(&point.row)
+            && (0..self.width as i64).contains(&point.col);
+        in_bounds.then(|| point.row as usize * self.width + point.col as usize)
+    }
+
+    pub fn point(&self, cell: usize) -> Point {
+        Point::new((cell / self.width) as i64, (cell % self.width) as i64)
+    }
+
+    pub fn is_wall(&self, cell: usize) -> bool {
+        self.walls.get(cell)
+    }
+
+    /// The cell next to `cell` in direction `facing`, if it's on the map.
+    pub fn step(&self, cell: usize, facing: usize) -> Option<usize> {
+        self.index(self.point(cell) + Direction::CARDINAL[facing])
+    }
+
+    /// Where a guard walking from `cell` stops in front of an obstacle, with one extra `obstruction`;
+    /// `None` if it walks off the map. The table only knows the original walls, so the obstruction is
+    /// patched in: it's only in the way if it's straight ahead, before the wall (or the edge).
+    fn jump(&self, cell: usize, facing: usize, obstruction: usize) -> Option<usize> {
+        let stop = self.jumps[facing][cell];
+        let (from, block) = (self.point(cell), self.point(obstruction));
+
+        // how many steps ahead the obstruction is:
+        let distance = match Direction::CARDINAL[facing] {
+            Direction::Up if block.col == from.col => from.row - block.row,
+            Direction::Down if block.col == from.col => block.row - from.row,
+            Direction::Left if block.row == from.row => from.col - block.col,
+            Direction::Right if block.row == from.row => block.col - from.col,
+            _ => 0,
+        };
+        let wall_distance = match stop {
+            EXIT => i64::MAX,
+            stop => self.point(stop as usize).manhattan(&from) + 1,
+        };
+
+        if distance > 0 && distance < wall_distance {
+            let before = block - Direction::CARDINAL[facing].delta();
+            return self.index(before);
+        }
+        (stop != EXIT).then_some(stop as usize)
+    }
+
+    /// Walk step by step from `guard` until it leaves the map or comes back to a state it's been in.
+    pub fn walk(&self, guard: Guard) -> Route {
+        const UNSEEN: usize = usize::MAX;
+
+        let mut visited = Bits::new(self.cells());
+        // the step every (cell, facing) was first reached at:
+        let mut first_seen = vec![UNSEEN; self.cells() * 4];
+        let mut entries = vec![];
+
+        let mut at = self.index(guard.position).expect("The guard is on the map");
+        let mut facing = facing_index(guard.facing);
+        visited.set(at);
+
+        for step in 0.. {
+            let state = at * 4 + facing;
+            if first_seen[state] != UNSEEN {
+                let outcome = Outcome::Loops {
+                    entry: self.point(at),
+                    facing: Direction::CARDINAL[facing],
+                    entered_at: first_seen[state],
+                    period: step - first_seen[state],
+                };
+                return Route {
+                    visited,
+                    entries,
+                    outcome,
+                };
+            }
+            first_seen[state] = step;
+
+            match self.step(at, facing) {
+                None => {
+                    let outcome = Outcome::Exits { steps: step + 1 };
+                    return Route {
+                        visited,
+                        entries,
+                        outcome,
+                    };
+                }
+                Some(ahead) if self.is_wall(ahead) => facing = (facing + 1) % 4,
+                Some(ahead) => {
+                    if visited.set(ahead) {
+                        entries.push(Entry {
+                            cell: ahead,
+                            from: at,
+                            facing,
+                        });
+                    }
+                    at = ahead;
+                }
+            }
+        }
+
+        unreachable!("Every walk ends, there are only so many states")
+    }
+
+    /// Whether an obstruction on `entry.cell` traps the guard. Everything up to the step onto it stays the same,
+    /// so the check starts right there, and jumps from obstacle to obstacle instead of walking.
+    pub fn loops_with(&self, entry: &Entry, seen: &mut Seen) -> bool {
+        seen.next_check();
+
+        let (mut at, mut facing) = (entry.from, entry.facing);
+        loop {
+            let Some(stop) = self.jump(at, facing, entry.cell) else {
+                return false;
+            };
+            at = stop;
+            facing = (facing + 1) % 4;
+
+            if seen.visit(at * 4 + facing) {
+                return true;
+            }
+        }
+    }
+}
+
+fn facing_index(direction: Direction) -> usize {
+    Direction::CARDINAL
+        .iter()
+        .position(|&cardinal| cardinal == direction)
+        .expect("Guards only face up, right, down or left")
+}
+
+// -- tests --
+
+#[test]
+fn test_jumps() {
+    let grid = Grid::chars(["..#.", "....", "#...", "...#"]).expect("Rectangular");
+    let lab = Lab::new(&grid);
+    let cell = |row, col| lab.index(Point::new(row, col)).expect("On the map");
+
+    // (3, 2) going up stops below the wall at (0, 2):
+    assert_eq!(lab.jumps[0][cell(3, 2)], cell(1, 2) as u32);
+    assert_eq!(lab.jumps[1][cell(3, 0)], cell(3, 2) as u32);
+    assert_eq!(lab.jumps[3][cell(2, 3)], cell(2, 1) as u32);
+    assert_eq!(lab.jumps[2][cell(0, 0)], cell(1, 0) as u32);
+    assert_eq!(lab.jumps[0][cell(3, 1)], EXIT);
+
+    // an obstruction ahead, closer than the wall, is in the way:
+    assert_eq!(lab.jump(cell(3, 2), 0, cell(2, 2)), Some(cell(3, 2)));
+    assert_eq!(lab.jump(cell(3, 1), 0, cell(1, 1)), Some(cell(2, 1)));
+    // behind the wall, behind the guard, or off to the side, it isn't:
+    assert_eq!(lab.jump(cell(3, 2), 0, cell(0, 2)), Some(cell(1, 2)));
+    assert_eq!(lab.jump(cell(1, 1), 0, cell(2, 1)), None);
+    assert_eq!(lab.jump(cell(3, 1), 0, cell(1, 0)), None);
+}
+
+#[test]
+fn test_outcomes() {
+    let boxed = Grid::chars([".#..", "#.#.", ".#..", "...."]).expect("Rectangular");
+    let lab = Lab::new(&boxed);
+    let guard = Guard {
+        position: Point::new(1, 1),
+        facing: Direction::Left,
+    };
+    // walled in, it just turns round and round:
+    assert_eq!(
+        lab.walk(guard).outcome,
+        Outcome::Loops {
+            entry: Point::new(1, 1),
+            facing: Direction::Left,
+            entered_at: 0,
+            period: 4
+        }
+    );
+
+    let guard = Guard {
+        position: Point::new(3, 0),
+        facing: Direction::Right,
+    };
+    let route = lab.walk(guard);
+    assert_eq!(route.outcome, Outcome::Exits { steps: 4 });
+    assert_eq!(route.visited.count(), 4);
+}
@@ -1,182 +1,329 @@
 #![allow(dead_code)]
 
-use std::collections::{HashMap, HashSet};
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
-
-#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
-enum Direction {
-    Up,
-    Right,
-    Down,
-    Left,
-}
-
-impl Direction {
-    fn next(&self) -> Self {
-        match self {
-            Self::Up => Self::Right,
-            Self::Right => Self::Down,
-            Self::Down => Self::Left,
-            Self::Left => Self::Up,
-        }
+use aoc_common::{
+    Answer, AocResult, Direction, Explain, Format, Grid, ParseError, PuzzleInput, Solution,
+};
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+mod lab;
+mod report;
+
+pub use lab::{Bits, Entry, Guard, Lab, Outcome, Route, Seen};
+pub use report::{Ending, GuardReport, Overlap, PatrolReport};
+
+type Input = (Vec<Guard>, Lab);
+
+/// Every `^`, `>`, `v` or `<` is a guard, facing that way; they don't get in each other's way.
+fn parse_matrix(input: &PuzzleInput) -> Result<Input, ParseError> {
+    let matrix = Grid::chars(input.lines())?;
+    let guards: Vec<Guard> = matrix
+        .iter()
+        .filter_map(|(position, &cell)| {
+            Some(Guard {
+                position,
+                facing: Direction::from_char(cell)?,
+            })
+        })
+        .collect();
+
+    if guards.is_empty() {
+        return Err(ParseError::new(
+            "",
+            "a guard (`^`, `>`, `v` or `<`) somewhere on the map",
+        ));
     }
+    Ok((guards, Lab::new(&matrix)))
 }
 
-type Location = (i64, i64);
-type Matrix = HashMap<Location, char>;
-type Guard = Location;
-
-fn walk_route(mut guard: Guard, matrix: &Matrix, points_visited: &mut HashSet<Location>) -> bool {
-    let mut direction = Direction::Up;
-    let mut points_visited_with_direction: HashSet<(Location, Direction)> = HashSet::new();
-
-    loop {
-        points_visited.insert(guard);
-
-        if points_visited_with_direction.contains(&(guard, direction)) {
-            // loop detected!
-            return true;
-        }
-
-        points_visited_with_direction.insert((guard, direction));
-
-        let next_location: Location = match direction {
-            Direction::Up => (guard.0 - 1, guard.1),
-            Direction::Right => (guard.0, guard.1 + 1),
-            Direction::Down => (guard.0 + 1, guard.1),
-            Direction::Left => (guard.0, guard.1 - 1),
-        };
-
-        let point = matrix.get(&next_location);
-
-        match point {
-            None => {
-                // out of bounds, stop!
-                return false;
-            }
-            Some('#') => {
-                // turn
-                direction = direction.next();
-            }
-            Some(_) => {
-                // . or ^, just walk
-                guard = next_location;
-            }
+/// The cells any guard visits, including the ones a guard that never gets out goes round.
+fn simple((guards, lab): &Input) -> AocResult {
+    let mut visited = Bits::new(lab.cells());
+    for &guard in guards {
+        let route = lab.walk(guard);
+        for cell in (0..lab.cells()).filter(|&cell| route.visited.get(cell)) {
+            visited.set(cell);
         }
     }
-}
-
-fn parse_matrix(file: FileHandle) -> (Guard, Matrix) {
-    let mut matrix = Matrix::new();
-    let mut guard: Guard = (0, 0);
 
-    for (y, line) in file.map_while(Result::ok).enumerate() {
-        for (x, char) in line.chars().enumerate() {
-            let x = x as i64;
-            let y = y as i64;
+    Ok(visited.count() as i64)
+}
 
-            if char == '^' {
-                guard = (y, x)
-            }
+/// The cells where one obstruction gets any guard stuck in a loop. Guards that go round in a loop without
+/// one aren't counted: almost any obstruction on their route would "trap" them.
+fn advanced((guards, lab): &Input) -> AocResult {
+    let mut starts = Bits::new(lab.cells());
+    for guard in guards {
+        starts.set(lab.index(guard.position).expect("Guards are on the map"));
+    }
 
-            matrix.insert((y, x), char);
-        }
+    // only obstructions on a route change it, and there's no room for one where a guard starts:
+    let entries: Vec<Entry> = guards
+        .iter()
+        .map(|&guard| lab.walk(guard))
+        .filter(Route::exits)
+        .flat_map(|route| route.entries)
+        .filter(|entry| !starts.get(entry.cell))
+        .collect();
+
+    #[cfg(feature = "rayon")]
+    let traps: Vec<usize> = entries
+        .par_iter()
+        .map_init(
+            || Seen::new(lab),
+            |seen, entry| (entry, lab.loops_with(entry, seen)),
+        )
+        .filter_map(|(entry, loops)| loops.then_some(entry.cell))
+        .collect();
+
+    #[cfg(not(feature = "rayon"))]
+    let traps: Vec<usize> = {
+        let mut seen = Seen::new(lab);
+        entries
+            .iter()
+            .filter(|entry| lab.loops_with(entry, &mut seen))
+            .map(|entry| entry.cell)
+            .collect()
+    };
+
+    // several guards can get stuck on the same one:
+    let mut unique = Bits::new(lab.cells());
+    for cell in traps {
+        unique.set(cell);
     }
 
-    (guard, matrix)
+    Ok(unique.count() as i64)
 }
 
-async fn simple(file: FileHandle) -> anyhow::Result<i64> {
-    let (guard, matrix) = parse_matrix(file);
+pub struct Day6;
+
+impl Solution for Day6 {
+    const DAY: u8 = 6;
 
-    let mut points_visited = HashSet::new();
-    let loop_detected = walk_route(guard, &matrix, &mut points_visited);
+    type Input = Input;
+    type Config = ();
 
-    if loop_detected {
-        panic!("This is only supposed to happen in part 2!")
+    fn parse(input: &PuzzleInput) -> AocResult<Self::Input> {
+        Ok(parse_matrix(input)?)
     }
 
-    Ok(points_visited.len() as i64)
-}
+    fn part1(input: &Self::Input, _config: &()) -> AocResult<Answer> {
+        Ok(simple(input)?.into())
+    }
 
-async fn advanced(file: FileHandle) -> anyhow::Result<i64> {
-    let (guard, matrix) = parse_matrix(file);
+    fn part2(input: &Self::Input, _config: &()) -> AocResult<Answer> {
+        Ok(advanced(input)?.into())
+    }
 
-    let mut initial_route = HashSet::new();
-    walk_route(guard, &matrix, &mut initial_route);
+    /// Where every guard went, whether it got out, and how much their routes overlap.
+    fn explain((guards, lab): &Self::Input, _config: &(), options: &Explain) -> AocResult<String> {
+        let report = PatrolReport::new(guards, lab);
 
-    let mut loops = 0;
-    for point in &initial_route {
-        let current_char = matrix.get(point).expect("Matrix should have point");
-        if current_char != &'.' {
-            // irrelevant, skip!
-            continue;
-        }
-        let mut matrix_with_obstruction = matrix.clone();
-        matrix_with_obstruction.insert(*point, '#');
-
-        if walk_route(guard, &matrix_with_obstruction, &mut HashSet::new()) {
-            // loop detected!
-            loops += 1;
-        }
+        Ok(match options.format {
+            Format::Text => report.to_string(),
+            Format::Json => serde_json::to_string_pretty(&report)? + "\n",
+            format => anyhow::bail!("Day {} has no {format} report", Self::DAY),
+        })
     }
-
-    Ok(loops)
 }
 
 // -- tests --
 
-type FileHandle = io::Lines<io::BufReader<File>>;
-
-async fn read_lines<P: AsRef<Path>>(filename: P) -> io::Result<FileHandle> {
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
-}
-
-#[tokio::test]
-async fn test_simple_minimal() {
+#[test]
+fn test_simple_minimal() {
     let answer = 41;
 
-    let file = read_lines("minimal.txt")
-        .await
-        .expect("Should be able to read minimal.txt");
+    let file = aoc_common::input_or_skip!(Day6, "example");
 
-    assert_eq!(simple(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day6::solve(1, &file).expect("Oof 1"), answer);
 }
 
-#[tokio::test]
-async fn test_simple() {
+#[test]
+fn test_simple() {
     let answer = 4982;
 
-    let file = read_lines("simple.txt")
-        .await
-        .expect("Should be able to read simple.txt");
+    let file = aoc_common::input_or_skip!(Day6, "input");
 
-    assert_eq!(simple(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day6::solve(1, &file).expect("Oof 1"), answer);
 }
 
-#[tokio::test]
-async fn test_advanced_minimal() {
+#[test]
+fn test_advanced_minimal() {
     let answer = 6;
 
-    let file = read_lines("minimal.txt")
-        .await
-        .expect("Should be able to read minimal.txt");
+    let file = aoc_common::input_or_skip!(Day6, "example");
 
-    assert_eq!(advanced(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day6::solve(2, &file).expect("Oof 1"), answer);
 }
 
-#[tokio::test]
-async fn test_advanced() {
+#[test]
+fn test_advanced() {
     let answer = 1663;
 
-    // note: run with `cargo test --release` or it will take ages!
+    let file = aoc_common::input_or_skip!(Day6, "input");
+
+    assert_eq!(Day6::solve(2, &file).expect("Oof 2"), answer);
+}
+
+#[test]
+fn test_matches_step_by_step() {
+    use aoc_common::Point;
+    use std::collections::HashSet;
+
+    // the simulation as it used to be: one step at a time, remembering every state
+    fn walks_in_circles(grid: &Grid<char>, mut at: Point) -> (bool, usize) {
+        let mut direction = Direction::Up;
+        let mut visited = HashSet::new();
+        let mut states = HashSet::new();
+        loop {
+            visited.insert(at);
+            if !states.insert((at, direction)) {
+                return (true, visited.len());
+            }
+            match grid.get(at + direction) {
+                None => return (false, visited.len()),
+                Some('#') => direction = direction.turn_right(),
+                Some(_) => at += direction,
+            }
+        }
+    }
 
-    let file = read_lines("simple.txt")
-        .await
-        .expect("Should be able to read simple.txt");
+    let mut seed = 7u64;
+    let mut random = |below: u64| {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (seed >> 33) % below
+    };
+
+    for _ in 0..200 {
+        let (width, height) = (3 + random(10) as usize, 3 + random(10) as usize);
+        let mut grid = Grid::new(width, height, '.');
+        for point in grid.points().collect::<Vec<_>>() {
+            if random(100) < 18 {
+                grid[point] = '#';
+            }
+        }
+        let start = Point::new(random(height as u64) as i64, random(width as u64) as i64);
+        grid[start] = '^';
+
+        let input = PuzzleInput::from(grid.to_string());
+        let (loops, visited) = walks_in_circles(&grid, start);
+        assert_eq!(
+            Day6::solve(1, &input).expect("Oof 1"),
+            visited as i64,
+            "{grid}"
+        );
+
+        // a guard that already loops can't be trapped any further
+        let expected = if loops {
+            0
+        } else {
+            grid.points()
+                .filter(|&point| grid[point] == '.')
+                .filter(|&point| {
+                    let mut blocked = grid.clone();
+                    blocked[point] = '#';
+                    walks_in_circles(&blocked, start).0
+                })
+                .count()
+        };
+        assert_eq!(
+            Day6::solve(2, &input).expect("Oof 2"),
+            expected as i64,
+            "{grid}"
+        );
+    }
+}
+
+#[test]
+fn test_several_guards() {
+    // the first guard goes round a square, the second walks straight out through it:
+    let input = PuzzleInput::from(
+        "\
+..#...
+.....#
+......
+.#^...
+....#.
+...^..
+",
+    );
+    let (guards, lab) = Day6::parse(&input).expect("Two guards");
+    assert_eq!(guards.len(), 2);
+    assert_eq!(Day6::solve(1, &input).expect("Oof 1"), 12);
+
+    let report = PatrolReport::new(&guards, &lab);
+    assert_eq!(
+        report.guards[0].ending,
+        Ending::Loops {
+            entry: (3, 2),
+            facing: '^',
+            entered_at: 0,
+            period: 12
+        }
+    );
+    assert_eq!(report.guards[1].ending, Ending::Exits { steps: 6 });
+    assert_eq!(report.guards[1].visited.len(), 6);
+    assert_eq!((report.visited, report.shared), (12, 2));
+    assert_eq!(
+        report.overlaps,
+        vec![Overlap {
+            guards: (1, 2),
+            cells: 2
+        }]
+    );
+
+    let text = Day6::explain(&(guards, lab), &(), &Format::Text.into()).expect("Explainable");
+    assert!(
+        text.contains("guard 1 (^) from (3, 2): 8 cells, loops from step 0 at (3, 2) facing ^, every 12 steps"),
+        "{text}"
+    );
+
+    // joining the loop a couple of steps in:
+    let late = PuzzleInput::from("..#...\n.....#\n......\n.#....\n....#.\n..^...\n");
+    let (guards, lab) = Day6::parse(&late).expect("A guard");
+    let json = Day6::explain(&(guards, lab), &(), &Format::Json.into()).expect("Explainable");
+    assert!(json.contains(r#""outcome": "loops""#), "{json}");
+    assert!(json.contains(r#""entered_at": 2"#), "{json}");
+
+    let sideways = PuzzleInput::from("....\n.<..\n....\n");
+    assert_eq!(Day6::solve(1, &sideways).expect("Oof 1"), 2);
+    assert!(Day6::parse(&PuzzleInput::from("...\n.#.\n")).is_err());
+}
+
+#[test]
+fn test_guard_already_looping() {
+    // the first guard goes round a square whatever happens, the second walks straight out:
+    let input = PuzzleInput::from(
+        "\
+..#...
+.....#
+......
+.#^...
+....#.
+...^..
+",
+    );
+    let (guards, lab) = Day6::parse(&input).expect("Two guards");
+    assert!(!lab.walk(guards[0]).exits());
+    assert!(lab.walk(guards[1]).exits());
+
+    // only obstructions that trap the second guard count:
+    let mut traps = vec![];
+    let grid = Grid::chars(input.lines()).expect("Rectangular");
+    for point in grid.points() {
+        if grid[point] == '.' {
+            let mut blocked = grid.clone();
+            blocked[point] = '#';
+            if !Lab::new(&blocked).walk(guards[1]).exits() {
+                traps.push(point);
+            }
+        }
+    }
 
-    assert_eq!(advanced(file).await.expect("Oof 2"), answer);
+    assert_eq!(Day6::solve(2, &input).expect("Oof 2"), traps.len() as i64);
+    // blocking its way out at the top sends it round the first guard's square:
+    assert_eq!(traps, vec![aoc_common::Point::new(0, 3)]);
 }
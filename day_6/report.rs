@@ -0,0 +1,160 @@
+use crate::{Guard, Lab, Outcome};
+use aoc_common::{Direction, Point};
+use serde::Serialize;
+use std::fmt::{self, Display};
+
+fn arrow(direction: Direction) -> char {
+    direction.as_char().unwrap_or('?')
+}
+
+/// How one guard's patrol went.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GuardReport {
+    /// 1-based, in reading order of where they start.
+    pub guard: usize,
+    pub start: (i64, i64),
+    pub facing: char,
+    /// Every cell it stood on, in reading order, as (row, col).
+    pub visited: Vec<(i64, i64)>,
+    #[serde(flatten)]
+    pub ending: Ending,
+}
+
+/// `Outcome`, with plain positions and arrows.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum Ending {
+    /// Walked off the map after `steps` moves and turns.
+    Exits { steps: usize },
+    /// From step `entered_at` on, standing on `entry` facing `facing`, it repeats the same `period` steps.
+    Loops {
+        entry: (i64, i64),
+        facing: char,
+        entered_at: usize,
+        period: usize,
+    },
+}
+
+impl Display for Ending {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Ending::Exits { steps } => write!(f, "exits after {steps} steps"),
+            Ending::Loops {
+                entry: (row, col),
+                facing,
+                entered_at,
+                period,
+            } => write!(
+                f,
+                "loops from step {entered_at} at ({row}, {col}) facing {facing}, every {period} steps"
+            ),
+        }
+    }
+}
+
+/// How many cells two guards both visited.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Overlap {
+    pub guards: (usize, usize),
+    pub cells: usize,
+}
+
+/// Every guard's patrol, and where they got in each other's way.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PatrolReport {
+    pub guards: Vec<GuardReport>,
+    /// Cells any guard visited.
+    pub visited: usize,
+    /// Cells more than one guard visited.
+    pub shared: usize,
+    pub overlaps: Vec<Overlap>,
+}
+
+impl PatrolReport {
+    pub fn new(guards: &[Guard], lab: &Lab) -> Self {
+        let routes: Vec<_> = guards.iter().map(|&guard| lab.walk(guard)).collect();
+        let mut overlaps = vec![];
+        for (a, first) in routes.iter().enumerate() {
+            for (b, second) in routes.iter().enumerate().skip(a + 1) {
+                overlaps.push(Overlap {
+                    guards: (a + 1, b + 1),
+                    cells: first.visited.overlap(&second.visited),
+                });
+            }
+        }
+
+        let visits = |cell: usize| {
+            routes
+                .iter()
+                .filter(|route| route.visited.get(cell))
+                .count()
+        };
+        let visited = (0..lab.cells()).filter(|&cell| visits(cell) > 0).count();
+        let shared = (0..lab.cells()).filter(|&cell| visits(cell) > 1).count();
+
+        let as_pair = |point: Point| (point.row, point.col);
+        let guards = guards
+            .iter()
+            .zip(&routes)
+            .enumerate()
+            .map(|(idx, (guard, route))| GuardReport {
+                guard: idx + 1,
+                start: as_pair(guard.position),
+                facing: arrow(guard.facing),
+                visited: (0..lab.cells())
+                    .filter(|&cell| route.visited.get(cell))
+                    .map(|cell| as_pair(lab.point(cell)))
+                    .collect(),
+                ending: match route.outcome {
+                    Outcome::Exits { steps } => Ending::Exits { steps },
+                    Outcome::Loops {
+                        entry,
+                        facing,
+                        entered_at,
+                        period,
+                    } => Ending::Loops {
+                        entry: as_pair(entry),
+                        facing: arrow(facing),
+                        entered_at,
+                        period,
+                    },
+                },
+            })
+            .collect();
+
+        Self {
+            guards,
+            visited,
+            shared,
+            overlaps,
+        }
+    }
+}
+
+impl Display for PatrolReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for guard in &self.guards {
+            let (row, col) = guard.start;
+            writeln!(
+                f,
+                "guard {} ({}) from ({row}, {col}): {} cells, {}",
+                guard.guard,
+                guard.facing,
+                guard.visited.len(),
+                guard.ending
+            )?;
+        }
+
+        writeln!(
+            f,
+            "{} cells visited, {} by more than one guard",
+            self.visited, self.shared
+        )?;
+        for overlap in &self.overlaps {
+            let (a, b) = overlap.guards;
+            writeln!(f, "guards {a} and {b} share {} cells", overlap.cells)?;
+        }
+
+        Ok(())
+    }
+}
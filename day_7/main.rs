@@ -1,7 +1,7 @@
 #![allow(dead_code)]
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
+use aoc_common::{parse_in, Answer, AocResult, ParseError, PuzzleInput, Solution};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 
 #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
 enum Operation {
@@ -43,21 +43,23 @@ fn generate_combinations<T: Copy>(elements: &[T], n: usize) -> Vec<Vec<T>> {
     results
 }
 
-fn check_line(line: &str, operations: &[Operation]) -> i64 {
-    let mut parts = line.split(": ");
-
-    let sum: i64 = parts
-        .next()
-        .expect("Should have two parts")
-        .parse()
-        .expect("Should be a number");
-    let numbers: Vec<i64> = parts
-        .next()
-        .expect("Should have two parts")
+type Equation = (i64, Vec<i64>);
+
+fn parse_line(line: &str) -> Result<Equation, ParseError> {
+    let (sum, numbers) = line
+        .split_once(": ")
+        .ok_or_else(|| ParseError::in_line(line, line, "an equation like `190: 10 19`"))?;
+
+    let sum: i64 = parse_in(line, sum, "a number")?;
+    let numbers: Vec<i64> = numbers
         .split(" ")
-        .map(|n| n.parse().expect("Should be a number"))
-        .collect();
+        .map(|n| parse_in(line, n, "a number"))
+        .collect::<Result<_, _>>()?;
 
+    Ok((sum, numbers))
+}
+
+fn check_equation((sum, numbers): &Equation, operations: &[Operation]) -> i64 {
     let combinations = generate_combinations(operations, numbers.len() - 1);
     for combination in combinations {
         let mut numbers_i = numbers.iter();
@@ -68,83 +70,99 @@ fn check_line(line: &str, operations: &[Operation]) -> i64 {
             total = operation.apply(total, *number);
         }
 
-        if total == sum {
-            return sum;
+        if total == *sum {
+            return *sum;
         }
     }
 
     0
 }
 
-async fn simple(file: FileHandle) -> anyhow::Result<i64> {
-    let mut result = 0;
+/// The equations are independent, so with the `rayon` feature they're checked in parallel.
+fn calibration_result(equations: &[Equation], operations: &[Operation]) -> i64 {
+    #[cfg(feature = "rayon")]
+    let equations = equations.par_iter();
+    #[cfg(not(feature = "rayon"))]
+    let equations = equations.iter();
+
+    equations
+        .map(|equation| check_equation(equation, operations))
+        .sum()
+}
+
+fn simple(equations: &[Equation]) -> AocResult {
     let operations = vec![Operation::Add, Operation::Mul];
 
-    for line in file.map_while(Result::ok) {
-        result += check_line(&line, &operations);
-    }
-    Ok(result)
+    Ok(calibration_result(equations, &operations))
 }
 
-async fn advanced(file: FileHandle) -> anyhow::Result<i64> {
-    let mut result = 0;
+fn advanced(equations: &[Equation]) -> AocResult {
     let operations = vec![Operation::Add, Operation::Mul, Operation::Concat];
 
-    for line in file.map_while(Result::ok) {
-        result += check_line(&line, &operations);
-    }
-    Ok(result)
+    Ok(calibration_result(equations, &operations))
 }
 
-// -- tests --
+pub struct Day7;
 
-type FileHandle = io::Lines<io::BufReader<File>>;
+impl Solution for Day7 {
+    const DAY: u8 = 7;
 
-async fn read_lines<P: AsRef<Path>>(filename: P) -> io::Result<FileHandle> {
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
+    type Input = Vec<Equation>;
+    type Config = ();
+
+    fn parse(input: &PuzzleInput) -> AocResult<Self::Input> {
+        let equations = input
+            .lines()
+            .enumerate()
+            .map(|(idx, line)| parse_line(line).map_err(|err| err.on_line(idx + 1)))
+            .collect::<Result<_, _>>()?;
+
+        Ok(equations)
+    }
+
+    fn part1(input: &Self::Input, _config: &()) -> AocResult<Answer> {
+        Ok(simple(input)?.into())
+    }
+
+    fn part2(input: &Self::Input, _config: &()) -> AocResult<Answer> {
+        Ok(advanced(input)?.into())
+    }
 }
 
-#[tokio::test]
-async fn test_simple_minimal() {
+// -- tests --
+
+#[test]
+fn test_simple_minimal() {
     let answer = 3749;
 
-    let file = read_lines("minimal.txt")
-        .await
-        .expect("Should be able to read minimal.txt");
+    let file = aoc_common::input_or_skip!(Day7, "example");
 
-    assert_eq!(simple(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day7::solve(1, &file).expect("Oof 1"), answer);
 }
 
-#[tokio::test]
-async fn test_simple() {
+#[test]
+fn test_simple() {
     let answer = 1399219271639;
 
-    let file = read_lines("input.txt")
-        .await
-        .expect("Should be able to read input.txt");
+    let file = aoc_common::input_or_skip!(Day7, "input");
 
-    assert_eq!(simple(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day7::solve(1, &file).expect("Oof 1"), answer);
 }
 
-#[tokio::test]
-async fn test_advanced_minimal() {
+#[test]
+fn test_advanced_minimal() {
     let answer = 11387;
 
-    let file = read_lines("minimal.txt")
-        .await
-        .expect("Should be able to read minimal.txt");
+    let file = aoc_common::input_or_skip!(Day7, "example");
 
-    assert_eq!(advanced(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day7::solve(2, &file).expect("Oof 1"), answer);
 }
 
-#[tokio::test]
-async fn test_advanced() {
+#[test]
+fn test_advanced() {
     let answer = 275791737999003;
 
-    let file = read_lines("input.txt")
-        .await
-        .expect("Should be able to read input.txt");
+    let file = aoc_common::input_or_skip!(Day7, "input");
 
-    assert_eq!(advanced(file).await.expect("Oof 2"), answer);
+    assert_eq!(Day7::solve(2, &file).expect("Oof 2"), answer);
 }
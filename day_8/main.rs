@@ -1,9 +1,7 @@
 #![allow(dead_code)]
 
+use aoc_common::{Answer, AocResult, Grid, ParseError, Point, PuzzleInput, Solution};
 use std::collections::{HashMap, HashSet};
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
 
 fn generate_combinations<T: Copy + PartialEq>(elements: &[T], n: usize) -> Vec<Vec<T>> {
     if n == 0 {
@@ -23,38 +21,30 @@ fn generate_combinations<T: Copy + PartialEq>(elements: &[T], n: usize) -> Vec<V
     results
 }
 
-type Point = (i64, i64);
-type Matrix = HashMap<Point, char>;
+type Matrix = Grid<char>;
 type Antennae = HashMap<char, Vec<Point>>;
+type City = (Matrix, Antennae);
 
-fn parse_matrix(file: FileHandle) -> (Matrix, Antennae) {
-    let mut matrix: Matrix = Default::default();
+fn parse_matrix(input: &PuzzleInput) -> Result<City, ParseError> {
+    let matrix = Grid::chars(input.lines())?;
     let mut antennae: Antennae = Default::default();
 
-    for (x, line) in file.map_while(Result::ok).enumerate() {
-        for (y, char) in line.chars().enumerate() {
-            let point: Point = (x as i64, y as i64);
-            matrix.insert(point, char);
-
-            if char != '.' {
-                antennae.entry(char).or_default().push(point);
-            }
+    for (point, char) in matrix.iter() {
+        if *char != '.' {
+            antennae.entry(*char).or_default().push(point);
         }
     }
 
-    (matrix, antennae)
+    Ok((matrix, antennae))
 }
 
 fn find_antinode(p1: &Point, p2: &Point) -> Point {
-    let d_x = p2.0 - p1.0;
-    let d_y = p2.1 - p1.1;
+    let delta = *p2 - *p1;
 
-    (p1.0 - d_x, p1.1 - d_y)
+    *p1 - delta
 }
 
-async fn simple(file: FileHandle) -> anyhow::Result<i64> {
-    let (matrix, antennae) = parse_matrix(file);
-
+fn simple((matrix, antennae): &City) -> AocResult {
     // hashset to prevent duplicates:
     let mut antinodes: HashSet<Point> = Default::default();
 
@@ -65,7 +55,7 @@ async fn simple(file: FileHandle) -> anyhow::Result<i64> {
             let second = combi_i.next().expect("Should have 2 points");
 
             let antinode = find_antinode(first, second);
-            if matrix.contains_key(&antinode) {
+            if matrix.contains(antinode) {
                 antinodes.insert(antinode);
             }
         }
@@ -77,12 +67,11 @@ async fn simple(file: FileHandle) -> anyhow::Result<i64> {
 fn find_antinodes(p1: &Point, p2: &Point, matrix: &Matrix) -> HashSet<Point> {
     let mut nodes = HashSet::new();
 
-    let d_x = p2.0 - p1.0;
-    let d_y = p2.1 - p1.1;
+    let delta = *p2 - *p1;
 
     for mul in 0..10_000 {
-        let p3 = (p1.0 - d_x * mul, p1.1 - d_y * mul);
-        if !matrix.contains_key(&p3) {
+        let p3 = *p1 - delta * mul;
+        if !matrix.contains(p3) {
             // out of bounds, stop!
             break;
         }
@@ -92,9 +81,7 @@ fn find_antinodes(p1: &Point, p2: &Point, matrix: &Matrix) -> HashSet<Point> {
     nodes
 }
 
-async fn advanced(file: FileHandle) -> anyhow::Result<i64> {
-    let (matrix, antennae) = parse_matrix(file);
-
+fn advanced((matrix, antennae): &City) -> AocResult {
     // hashset to prevent duplicates:
     let mut antinodes: HashSet<Point> = Default::default();
 
@@ -103,7 +90,7 @@ async fn advanced(file: FileHandle) -> anyhow::Result<i64> {
             let mut combi_i = combination.iter();
             let first = combi_i.next().expect("Should have 2 points");
             let second = combi_i.next().expect("Should have 2 points");
-            antinodes.extend(find_antinodes(first, second, &matrix));
+            antinodes.extend(find_antinodes(first, second, matrix));
 
             dbg!(first, second);
         }
@@ -111,77 +98,79 @@ async fn advanced(file: FileHandle) -> anyhow::Result<i64> {
     Ok(antinodes.len() as i64)
 }
 
-// -- tests --
+pub struct Day8;
+
+impl Solution for Day8 {
+    const DAY: u8 = 8;
 
-type FileHandle = io::Lines<io::BufReader<File>>;
+    type Input = City;
+    type Config = ();
 
-async fn read_lines<P: AsRef<Path>>(filename: P) -> io::Result<FileHandle> {
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
+    fn parse(input: &PuzzleInput) -> AocResult<Self::Input> {
+        Ok(parse_matrix(input)?)
+    }
+
+    fn part1(input: &Self::Input, _config: &()) -> AocResult<Answer> {
+        Ok(simple(input)?.into())
+    }
+
+    fn part2(input: &Self::Input, _config: &()) -> AocResult<Answer> {
+        Ok(advanced(input)?.into())
+    }
 }
 
-// #[tokio::test]
-// async fn test_simple_very_minimal() {
+// -- tests --
+
+// #[test]
+// fn test_simple_very_minimal() {
 //     let answer = 2;
 //
-//     let file = read_lines("tiny.txt")
-//         .await
-//         .expect("Should be able to read tiny.txt");
+//     let file = aoc_common::input_or_skip!(Day8, "tiny");
 //
-//     assert_eq!(simple(file).await.expect("Oof 1"), answer);
+//     assert_eq!(Day8::solve(1, &file).expect("Oof 1"), answer);
 // }
 //
-// #[tokio::test]
-// async fn test_simple_minimal() {
+// #[test]
+// fn test_simple_minimal() {
 //     let answer = 14;
 //
-//     let file = read_lines("minimal.txt")
-//         .await
-//         .expect("Should be able to read minimal.txt");
+//     let file = aoc_common::input_or_skip!(Day8, "example");
 //
-//     assert_eq!(simple(file).await.expect("Oof 1"), answer);
+//     assert_eq!(Day8::solve(1, &file).expect("Oof 1"), answer);
 // }
 //
-// #[tokio::test]
-// async fn test_simple() {
+// #[test]
+// fn test_simple() {
 //     let answer = 367;
 //
-//     let file = read_lines("input.txt")
-//         .await
-//         .expect("Should be able to read input.txt");
+//     let file = aoc_common::input_or_skip!(Day8, "input");
 //
-//     assert_eq!(simple(file).await.expect("Oof 1"), answer);
+//     assert_eq!(Day8::solve(1, &file).expect("Oof 1"), answer);
 // }
 
-#[tokio::test]
-async fn test_advanced_very_minimal() {
+#[test]
+fn test_advanced_very_minimal() {
     let answer = 9;
 
-    let file = read_lines("advanced.txt")
-        .await
-        .expect("Should be able to read advanced.txt");
+    let file = aoc_common::input_or_skip!(Day8, "example2");
 
-    assert_eq!(advanced(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day8::solve(2, &file).expect("Oof 1"), answer);
 }
 
-#[tokio::test]
-async fn test_advanced_minimal() {
+#[test]
+fn test_advanced_minimal() {
     let answer = 34;
 
-    let file = read_lines("minimal.txt")
-        .await
-        .expect("Should be able to read minimal.txt");
+    let file = aoc_common::input_or_skip!(Day8, "example");
 
-    assert_eq!(advanced(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day8::solve(2, &file).expect("Oof 1"), answer);
 }
 
-#[tokio::test]
-async fn test_advanced() {
+#[test]
+fn test_advanced() {
     let answer = 1285;
 
-    let file = read_lines("input.txt")
-        .await
-        .expect("Should be able to read input.txt");
+    let file = aoc_common::input_or_skip!(Day8, "input");
 
-    assert_eq!(advanced(file).await.expect("Oof 2"), answer);
+    assert_eq!(Day8::solve(2, &file).expect("Oof 2"), answer);
 }
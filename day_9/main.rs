@@ -1,20 +1,20 @@
 #![allow(dead_code)]
 
+use aoc_common::{Answer, AocResult, ParseError, PuzzleInput, Solution};
 use std::collections::HashSet;
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
 
 type FlatDiskMap = Vec<i64>;
 type DiskMap = Vec<Option<i64>>;
 
-fn parse_disk_map(line: &str) -> DiskMap {
+fn parse_disk_map(line: &str) -> Result<DiskMap, ParseError> {
     let mut disk_map: DiskMap = vec![];
     let mut idx = 0;
 
     for (i, char) in line.chars().enumerate() {
         let is_data = i % 2 == 0;
-        let value = char.to_digit(10).expect("Should be a number") as i64;
+        let value =
+            char.to_digit(10)
+                .ok_or_else(|| ParseError::new(char, "a digit").at(1, i + 1))? as i64;
         for _ in 0..value {
             if is_data {
                 disk_map.push(Some(idx));
@@ -27,7 +27,7 @@ fn parse_disk_map(line: &str) -> DiskMap {
         }
     }
 
-    disk_map
+    Ok(disk_map)
 }
 
 fn find_index<T: PartialEq>(vec: &[T], name: &T) -> Option<usize> {
@@ -53,14 +53,10 @@ fn calculate_checksum(disk_map: &DiskMap) -> i64 {
     result
 }
 
-async fn simple(file: FileHandle) -> anyhow::Result<i64> {
-    if let Some(line) = file.map_while(Result::ok).next() {
-        let mut disk_map = parse_disk_map(&line);
-        optimize_disk_map(&mut disk_map);
-        let checksum = calculate_checksum(&disk_map);
-        return Ok(checksum);
-    }
-    Ok(0)
+fn simple(disk_map: &DiskMap) -> AocResult {
+    let mut disk_map = disk_map.clone();
+    optimize_disk_map(&mut disk_map);
+    Ok(calculate_checksum(&disk_map))
 }
 
 fn find_consecutive<T: PartialEq>(vec: &[T], n: usize, target: T) -> Option<usize> {
@@ -116,76 +112,81 @@ fn optimize_disk_map_by_file(disk_map: &mut DiskMap) {
     }
 }
 
-async fn advanced(file: FileHandle) -> anyhow::Result<i64> {
-    if let Some(line) = file.map_while(Result::ok).next() {
-        let mut disk_map = parse_disk_map(&line);
-        optimize_disk_map_by_file(&mut disk_map);
-        let checksum = calculate_checksum(&disk_map);
-        return Ok(checksum);
+fn advanced(disk_map: &DiskMap) -> AocResult {
+    if disk_map.is_empty() {
+        return Ok(0);
     }
-    Ok(0)
+
+    let mut disk_map = disk_map.clone();
+    optimize_disk_map_by_file(&mut disk_map);
+    Ok(calculate_checksum(&disk_map))
 }
 
-// -- tests --
+pub struct Day9;
 
-type FileHandle = io::Lines<io::BufReader<File>>;
+impl Solution for Day9 {
+    const DAY: u8 = 9;
+
+    type Input = DiskMap;
+    type Config = ();
+
+    fn parse(input: &PuzzleInput) -> AocResult<Self::Input> {
+        let line = input.lines().next().unwrap_or_default();
+        Ok(parse_disk_map(line)?)
+    }
 
-async fn read_lines<P: AsRef<Path>>(filename: P) -> io::Result<FileHandle> {
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
+    fn part1(input: &Self::Input, _config: &()) -> AocResult<Answer> {
+        Ok(simple(input)?.into())
+    }
+
+    fn part2(input: &Self::Input, _config: &()) -> AocResult<Answer> {
+        Ok(advanced(input)?.into())
+    }
 }
 
-#[tokio::test]
-async fn test_simple_very_minimal() {
+// -- tests --
+
+#[test]
+fn test_simple_very_minimal() {
     let answer = 60;
 
-    let file = read_lines("tiny.txt")
-        .await
-        .expect("Should be able to read tiny.txt");
+    let file = aoc_common::input_or_skip!(Day9, "tiny");
 
-    assert_eq!(simple(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day9::solve(1, &file).expect("Oof 1"), answer);
 }
 
-#[tokio::test]
-async fn test_simple_minimal() {
+#[test]
+fn test_simple_minimal() {
     let answer = 1928;
 
-    let file = read_lines("minimal.txt")
-        .await
-        .expect("Should be able to read minimal.txt");
+    let file = aoc_common::input_or_skip!(Day9, "example");
 
-    assert_eq!(simple(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day9::solve(1, &file).expect("Oof 1"), answer);
 }
 
-#[tokio::test]
-async fn test_simple() {
+#[test]
+fn test_simple() {
     let answer = 6211348208140;
 
-    let file = read_lines("input.txt")
-        .await
-        .expect("Should be able to read input.txt");
+    let file = aoc_common::input_or_skip!(Day9, "input");
 
-    assert_eq!(simple(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day9::solve(1, &file).expect("Oof 1"), answer);
 }
 
-#[tokio::test]
-async fn test_advanced_minimal() {
+#[test]
+fn test_advanced_minimal() {
     let answer = 2858;
 
-    let file = read_lines("minimal.txt")
-        .await
-        .expect("Should be able to read minimal.txt");
+    let file = aoc_common::input_or_skip!(Day9, "example");
 
-    assert_eq!(advanced(file).await.expect("Oof 1"), answer);
+    assert_eq!(Day9::solve(2, &file).expect("Oof 1"), answer);
 }
 
-#[tokio::test]
-async fn test_advanced() {
+#[test]
+fn test_advanced() {
     let answer = 6239783302560;
 
-    let file = read_lines("input.txt")
-        .await
-        .expect("Should be able to read input.txt");
+    let file = aoc_common::input_or_skip!(Day9, "input");
 
-    assert_eq!(advanced(file).await.expect("Oof 2"), answer);
+    assert_eq!(Day9::solve(2, &file).expect("Oof 2"), answer);
 }